@@ -0,0 +1,240 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, FileType, Metadata};
+
+use crate::{layered, paths};
+
+/// An [`AssetIo`] that serves some paths from other paths, e.g. `player.png`
+/// from `skins/default/bnnuy.png`.
+///
+/// Aliases can also name directories, in which case everything under the
+/// alias is served from under the target.
+pub(crate) struct AliasAssetIo {
+    inner: Box<dyn AssetIo>,
+    /// Each alias and its target, longest alias first.
+    aliases: Vec<(PathBuf, PathBuf)>,
+    loaded: AssetAliases,
+}
+
+impl AliasAssetIo {
+    pub fn new(inner: Box<dyn AssetIo>, aliases: &[(PathBuf, PathBuf)], loaded: AssetAliases) -> Self {
+        let mut aliases = aliases.to_vec();
+        aliases.sort_by_key(|(alias, _)| std::cmp::Reverse(alias.components().count()));
+        Self { inner, aliases, loaded }
+    }
+
+    /// Returns the path an alias points `path` to, or `path` itself.
+    fn resolve(&self, path: &Path) -> PathBuf {
+        let key = paths::normalize(path);
+        self.aliases
+            .iter()
+            .find_map(|(alias, target)| {
+                let rest = key.strip_prefix(alias).ok()?;
+                // joining an empty path would leave a trailing separator, which breaks file aliases on disk
+                Some(if rest.as_os_str().is_empty() {
+                    target.clone()
+                } else {
+                    target.join(rest)
+                })
+            })
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Returns the children of `dir` that are aliases or lead to one.
+    fn aliases_in(&self, dir: &Path) -> BTreeSet<PathBuf> {
+        self.aliases
+            .iter()
+            .filter_map(|(alias, _)| match alias.strip_prefix(dir).ok()?.components().next()? {
+                Component::Normal(child) => Some(dir.join(child)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl AssetIo for AliasAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            match self.inner.load_path(&self.resolve(path)).await {
+                Err(err) if layered::is_not_found(&err) => Err(AssetIoError::NotFound(path.to_path_buf())),
+                result => result,
+            }
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let dir = paths::normalize(path);
+        let resolved = self.resolve(&dir);
+        let mut entries = self.aliases_in(&dir);
+        let found = match self.inner.read_directory(&resolved) {
+            Ok(children) => {
+                // entries of an aliased directory are listed under the alias
+                entries.extend(children.filter_map(|child| Some(dir.join(child.strip_prefix(&resolved).ok()?))));
+                true
+            }
+            Err(err) if layered::is_not_found(&err) => false,
+            Err(err) => return Err(err),
+        };
+        if found || !entries.is_empty() {
+            Ok(Box::new(entries.into_iter()))
+        } else {
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        }
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        let key = paths::normalize(path);
+        // directories only containing aliases don't exist otherwise
+        let has_aliases = self
+            .aliases
+            .iter()
+            .any(|(alias, _)| *alias != key && alias.starts_with(&key));
+        match self.inner.get_metadata(&self.resolve(&key)) {
+            Err(err) if layered::is_not_found(&err) && has_aliases => Ok(Metadata::new(FileType::Directory)),
+            result => result,
+        }
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        // called for every path the asset server loads
+        let resolved = self.resolve(path);
+        self.loaded.request(path);
+        self.loaded.link(path, &resolved);
+        self.inner.watch_path_for_changes(&resolved)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.inner.watch_for_changes()
+    }
+}
+
+/// The paths the [`AssetServer`](bevy::prelude::AssetServer) loaded each
+/// asset under, so that changes to a file reload every handle served from it,
+/// including those loaded through an alias or as a variant.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct AssetAliases {
+    state: Arc<Mutex<AliasState>>,
+}
+
+#[derive(Default, Debug)]
+struct AliasState {
+    /// Every path the asset server has loaded.
+    requested: HashSet<PathBuf>,
+    /// The path each path was last served from, if it was a different one.
+    targets: HashMap<PathBuf, PathBuf>,
+    /// The paths that were served from each path.
+    aliases: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+impl AssetAliases {
+    /// Records that the asset server loaded `path`.
+    pub fn request(&self, path: &Path) {
+        self.state.lock().unwrap().requested.insert(path.to_path_buf());
+    }
+
+    /// Records that `path` was served from `target`.
+    pub fn link(&self, path: &Path, target: &Path) {
+        let target = paths::normalize(target);
+        let mut state = self.state.lock().unwrap();
+        // a variant can be served from a different file after its settings change
+        if let Some(previous) = state.targets.remove(path) {
+            if let Some(aliases) = state.aliases.get_mut(&previous) {
+                aliases.remove(path);
+            }
+        }
+        if target != paths::normalize(path) {
+            state.targets.insert(path.to_path_buf(), target.clone());
+            state.aliases.entry(target).or_default().insert(path.to_path_buf());
+        }
+    }
+
+    /// Returns every loaded path that is served from one of the `changed` paths.
+    pub fn requesters(&self, changed: impl IntoIterator<Item = PathBuf>) -> BTreeSet<PathBuf> {
+        let state = self.state.lock().unwrap();
+        let mut pending: Vec<_> = changed.into_iter().collect();
+        let mut seen = HashSet::new();
+        let mut requesters = BTreeSet::new();
+        while let Some(path) = pending.pop() {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            if let Some(aliases) = state.aliases.get(&paths::normalize(&path)) {
+                pending.extend(aliases.iter().cloned());
+            }
+            if state.requested.contains(&path) {
+                requesters.insert(path);
+            }
+        }
+        requesters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use bevy::asset::FileAssetIo;
+    use futures_lite::future::block_on;
+
+    use super::*;
+    use crate::MemoryAssets;
+
+    #[test]
+    fn aliases_resolve_on_disk() {
+        let dir = std::env::temp_dir().join(format!("bevy_include_assets_alias_{}", std::process::id()));
+        fs::create_dir_all(dir.join("skins/default")).unwrap();
+        fs::write(dir.join("skins/default/bnnuy.png"), b"bnnuy").unwrap();
+
+        let io = AliasAssetIo::new(
+            Box::new(FileAssetIo::new(&dir, false)),
+            &[
+                ("player.png".into(), "skins/default/bnnuy.png".into()),
+                ("skin".into(), "skins/default".into()),
+            ],
+            AssetAliases::default(),
+        );
+        let player = block_on(io.load_path(Path::new("player.png")));
+        let skin = block_on(io.load_path(Path::new("skin/bnnuy.png")));
+        let metadata = io.get_metadata(Path::new("player.png"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(player.unwrap(), b"bnnuy");
+        assert_eq!(skin.unwrap(), b"bnnuy");
+        assert!(metadata.unwrap().is_file());
+    }
+
+    #[test]
+    fn overriding_a_target_reloads_its_aliases() {
+        let memory = MemoryAssets::default();
+        memory.insert("skins/default/bnnuy.png", "bnnuy");
+        let loaded = AssetAliases::default();
+        let io = AliasAssetIo::new(
+            Box::new(memory.clone()),
+            &[("player.png".into(), "skins/default/bnnuy.png".into())],
+            loaded.clone(),
+        );
+        io.watch_path_for_changes(Path::new("player.png")).unwrap();
+
+        memory.insert("skins/default/bnnuy.png", "evil bnnuy");
+        let reloaded = loaded.requesters(memory.changed());
+
+        assert_eq!(reloaded, BTreeSet::from([PathBuf::from("player.png")]));
+    }
+
+    #[test]
+    fn unaliased_paths_reload_themselves() {
+        let memory = MemoryAssets::default();
+        memory.insert("bnnuy.png", "bnnuy");
+        let loaded = AssetAliases::default();
+        let io = AliasAssetIo::new(Box::new(memory.clone()), &[], loaded.clone());
+        io.watch_path_for_changes(Path::new("bnnuy.png")).unwrap();
+
+        memory.insert("bnnuy.png", "evil bnnuy");
+        memory.insert("unloaded.png", "bnnuy");
+        let reloaded = loaded.requesters(memory.changed());
+
+        assert_eq!(reloaded, BTreeSet::from([PathBuf::from("bnnuy.png")]));
+    }
+}
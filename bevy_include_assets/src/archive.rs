@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::{self, Debug, Formatter};
+#[cfg(feature = "signing")]
+use std::fs;
+use std::fs::File;
+#[cfg(feature = "signing")]
+use std::io::Cursor;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bevy::app::{App, PluginGroupBuilder};
+use bevy::asset::{create_platform_default_asset_io, AssetIo, AssetIoError, AssetPlugin, BoxedFuture, Metadata};
+use bevy::log::error;
+use bevy::prelude::{AssetServer, Plugin};
+
+use crate::index::AssetIndex;
+#[cfg(feature = "signing")]
+use crate::signing::{self, PackVerifier};
+use crate::{paths, EmbeddedAssetIo};
+
+/// A seekable source of archive data.
+pub trait ArchiveReader: Read + Seek + Send + 'static {}
+
+impl<T: Read + Seek + Send + 'static> ArchiveReader for T {}
+
+/// An [`AssetIo`] that serves assets out of a single zip or tar archive.
+///
+/// The archive's table of contents is read once when it is opened, and
+/// individual assets are then read from it on demand.
+pub struct ArchiveAssetIo {
+    index: AssetIndex,
+    archive: Archive,
+}
+
+enum Archive {
+    #[cfg(feature = "zip")]
+    Zip {
+        archive: Mutex<zip::ZipArchive<Box<dyn ArchiveReader>>>,
+        /// The index of each file within the archive.
+        files: HashMap<PathBuf, usize>,
+    },
+    #[cfg(feature = "tar")]
+    Tar {
+        reader: Mutex<Box<dyn ArchiveReader>>,
+        /// The offset and size of each file within the archive.
+        files: HashMap<PathBuf, (u64, u64)>,
+    },
+}
+
+impl ArchiveAssetIo {
+    /// Opens the archive at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Opens the archive at `path` as long as `verifier` accepts its signature,
+    /// which is kept next to it as `<archive>.sig`.
+    ///
+    /// The archive is read into memory and served from there, so that it
+    /// can't be swapped out after being verified.
+    #[cfg(feature = "signing")]
+    pub fn open_verified(path: impl AsRef<Path>, verifier: &PackVerifier) -> io::Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        verifier.check(path, &data, signing::read_signature(path)?.as_ref())?;
+        Self::new(Cursor::new(data))
+    }
+
+    /// Reads an archive from `reader`, detecting its format from its contents.
+    pub fn new(mut reader: impl ArchiveReader) -> io::Result<Self> {
+        let mut header = [0; 512];
+        let len = read_up_to(&mut reader, &mut header)?;
+        reader.seek(SeekFrom::Start(0))?;
+        let reader = Box::new(reader) as Box<dyn ArchiveReader>;
+        let header = &header[..len];
+
+        #[cfg(feature = "zip")]
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            return Self::zip(reader);
+        }
+        #[cfg(feature = "tar")]
+        if header.get(257..262) == Some(&b"ustar"[..]) {
+            return Self::tar(reader);
+        }
+
+        Err(io::Error::new(io::ErrorKind::InvalidData, "unsupported archive format"))
+    }
+
+    #[cfg(feature = "zip")]
+    fn zip(reader: Box<dyn ArchiveReader>) -> io::Result<Self> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+        let mut files = HashMap::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index_raw(i)?;
+            if file.is_file() {
+                files.insert(paths::normalize(Path::new(file.name())), i);
+            }
+        }
+        Ok(Self {
+            index: AssetIndex::new(files.keys()),
+            archive: Archive::Zip {
+                archive: Mutex::new(archive),
+                files,
+            },
+        })
+    }
+
+    #[cfg(feature = "tar")]
+    fn tar(reader: Box<dyn ArchiveReader>) -> io::Result<Self> {
+        let mut archive = tar::Archive::new(reader);
+        let mut files = HashMap::new();
+        for entry in archive.entries_with_seek()? {
+            let entry = entry?;
+            if entry.header().entry_type().is_file() {
+                let path = paths::normalize(&entry.path()?);
+                files.insert(path, (entry.raw_file_position(), entry.size()));
+            }
+        }
+        Ok(Self {
+            index: AssetIndex::new(files.keys()),
+            archive: Archive::Tar {
+                reader: Mutex::new(archive.into_inner()),
+                files,
+            },
+        })
+    }
+
+    /// Returns the path of every file in the archive, in no particular order.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.index.files()
+    }
+
+    /// Returns the uncompressed size of a file in the archive, or `None` if it doesn't exist.
+    pub fn size(&self, path: &Path) -> Option<u64> {
+        let key = self.index.key(path);
+        match &self.archive {
+            #[cfg(feature = "zip")]
+            Archive::Zip { archive, files } => {
+                let index = *files.get(&key)?;
+                let mut archive = archive.lock().unwrap();
+                let size = archive.by_index_raw(index).ok()?.size();
+                Some(size)
+            }
+            #[cfg(feature = "tar")]
+            Archive::Tar { files, .. } => files.get(&key).map(|(_, size)| *size),
+        }
+    }
+
+    /// Whether files are checked against checksums as they are read, which
+    /// they are in zip archives but not in tar archives.
+    pub fn has_checksums(&self) -> bool {
+        match &self.archive {
+            #[cfg(feature = "zip")]
+            Archive::Zip { .. } => true,
+            #[cfg(feature = "tar")]
+            Archive::Tar { .. } => false,
+        }
+    }
+
+    /// Reads a file out of the archive, returning `None` if it doesn't exist.
+    pub fn read_file(&self, path: &Path) -> io::Result<Option<Vec<u8>>> {
+        let key = self.index.key(path);
+        match &self.archive {
+            #[cfg(feature = "zip")]
+            Archive::Zip { archive, files } => {
+                let index = match files.get(&key) {
+                    Some(index) => *index,
+                    None => return Ok(None),
+                };
+                let mut archive = archive.lock().unwrap();
+                let mut file = archive.by_index(index)?;
+                let mut data = Vec::with_capacity(file.size() as usize);
+                file.read_to_end(&mut data)?;
+                Ok(Some(data))
+            }
+            #[cfg(feature = "tar")]
+            Archive::Tar { reader, files } => {
+                let (offset, size) = match files.get(&key) {
+                    Some(file) => *file,
+                    None => return Ok(None),
+                };
+                let mut reader = reader.lock().unwrap();
+                reader.seek(SeekFrom::Start(offset))?;
+                let mut data = vec![0; size as usize];
+                reader.read_exact(&mut data)?;
+                Ok(Some(data))
+            }
+        }
+    }
+}
+
+impl AssetIo for ArchiveAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            match self.read_file(path)? {
+                Some(data) => Ok(data),
+                None => Err(AssetIoError::NotFound(path.to_path_buf())),
+            }
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.index.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        self.index.get_metadata(path)
+    }
+
+    fn watch_path_for_changes(&self, _path: &Path) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
+
+impl Debug for ArchiveAssetIo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveAssetIo").field("index", &self.index).finish()
+    }
+}
+
+/// Serves assets out of an archive instead of the asset folder.
+///
+/// Relative paths are resolved against the executable's directory, so a
+/// pack like `assets.pak` can be shipped next to the game and patched
+/// without recompiling it. If the archive can't be opened, the asset folder
+/// is used instead, unless a signature is required, in which case no assets
+/// are served at all.
+///
+/// The plugin has to be added before Bevy's `AssetPlugin`:
+///
+/// ```ignore
+/// App::new().add_plugins_with(DefaultPlugins, |group| ArchiveAssetsPlugin::new("assets.zip").add_to(group))
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArchiveAssetsPlugin {
+    path: PathBuf,
+    #[cfg(feature = "signing")]
+    verifier: Option<PackVerifier>,
+}
+
+impl ArchiveAssetsPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            #[cfg(feature = "signing")]
+            verifier: None,
+        }
+    }
+
+    /// Requires the archive to be signed, with its signature kept next to it
+    /// as `<archive>.sig`.
+    #[cfg(feature = "signing")]
+    pub fn with_verifier(mut self, verifier: PackVerifier) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    /// Adds this plugin to a plugin group like `DefaultPlugins`, before the
+    /// `AssetPlugin` it replaces the asset server of.
+    pub fn add_to(self, group: &mut PluginGroupBuilder) -> &mut PluginGroupBuilder {
+        group.add_before::<AssetPlugin, _>(self)
+    }
+
+    /// Whether the archive is only served if its signature checks out.
+    fn requires_signature(&self) -> bool {
+        #[cfg(feature = "signing")]
+        let required = self.verifier.is_some();
+        #[cfg(not(feature = "signing"))]
+        let required = false;
+        required
+    }
+}
+
+impl Plugin for ArchiveAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        let path = base_path().join(&self.path);
+        #[cfg(feature = "signing")]
+        let archive = match &self.verifier {
+            Some(verifier) => ArchiveAssetIo::open_verified(&path, verifier),
+            None => ArchiveAssetIo::open(&path),
+        };
+        #[cfg(not(feature = "signing"))]
+        let archive = ArchiveAssetIo::open(&path);
+        let asset_io: Box<dyn AssetIo> = match archive {
+            Ok(archive) => Box::new(archive),
+            Err(err) if self.requires_signature() => {
+                error!("Couldn't open signed asset archive {}: {}", path.display(), err);
+                // Falling back to unsigned assets would defeat the signature.
+                Box::<EmbeddedAssetIo>::default()
+            }
+            Err(err) => {
+                error!("Couldn't open asset archive {}: {}", path.display(), err);
+                create_platform_default_asset_io(app)
+            }
+        };
+        app.insert_resource(AssetServer::with_boxed_io(asset_io));
+    }
+
+    fn name(&self) -> &str {
+        "ArchiveAssetsPlugin"
+    }
+}
+
+/// Like [`Read::read_exact`], but stops early at the end of the stream.
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(read) => len += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
+
+/// The directory that relative asset paths are resolved against, which is
+/// the same one `FileAssetIo` uses.
+fn base_path() -> PathBuf {
+    match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => PathBuf::from(manifest_dir),
+        None => env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_default(),
+    }
+}
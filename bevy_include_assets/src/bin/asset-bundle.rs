@@ -0,0 +1,278 @@
+//! Manages the assets packaged into game executables.
+//!
+//! ```text
+//! asset-bundle append <executable> <archive>
+//! asset-bundle list <file>
+//! asset-bundle extract <file> <dir>
+//! asset-bundle verify <file>
+//! ```
+//!
+//! `list`, `extract` and `verify` work on executables, which may contain
+//! assets embedded by `include_assets!` as well as an appended archive,
+//! and on plain archives.
+//!
+//! With the `signing` feature, archives can also be signed with a 32-byte
+//! ed25519 secret key (e.g. from `head -c 32 /dev/urandom`):
+//!
+//! ```text
+//! asset-bundle public-key <secret-key> <public-key>
+//! asset-bundle sign <archive> <secret-key>
+//! asset-bundle verify-signature <file> <public-key>
+//! ```
+//!
+//! `sign` writes `<archive>.sig`, which `append` picks up automatically.
+
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+use std::{fs, io};
+
+use bevy_include_assets::{append_bundle, EmbeddedAssets};
+#[cfg(feature = "signing")]
+use bevy_include_assets::{find_bundle, public_key, sign_pack, signature_path, verify_bundle, PackVerifier};
+#[cfg(any(feature = "zip", feature = "tar"))]
+use bevy_include_assets::{open_bundle, ArchiveAssetIo};
+
+const USAGE: &str = "\
+usage: asset-bundle append <executable> <archive>
+       asset-bundle list <file>
+       asset-bundle extract <file> <dir>
+       asset-bundle verify <file>";
+
+#[cfg(feature = "signing")]
+const SIGNING_USAGE: &str = "\
+       asset-bundle public-key <secret-key> <public-key>
+       asset-bundle sign <archive> <secret-key>
+       asset-bundle verify-signature <file> <public-key>";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let result = match args[..] {
+        ["append", executable, archive] => append_bundle(Path::new(executable), Path::new(archive)),
+        ["list", file] => list(Path::new(file)),
+        ["extract", file, dir] => extract(Path::new(file), Path::new(dir)),
+        ["verify", file] => verify(Path::new(file)),
+        #[cfg(feature = "signing")]
+        ["public-key", secret_key, output] => {
+            read_key(Path::new(secret_key)).and_then(|key| fs::write(output, public_key(&key)?))
+        }
+        #[cfg(feature = "signing")]
+        ["sign", archive, secret_key] => sign(Path::new(archive), Path::new(secret_key)),
+        #[cfg(feature = "signing")]
+        ["verify-signature", file, key] => verify_signature(Path::new(file), Path::new(key)),
+        _ => {
+            eprintln!("{}", USAGE);
+            #[cfg(feature = "signing")]
+            eprintln!("{}", SIGNING_USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn list(file: &Path) -> io::Result<()> {
+    for source in Source::open(file)? {
+        println!("{}:", source.name());
+        for path in source.files() {
+            match source.size(&path) {
+                Some(size) => println!("  {} ({} bytes)", path.display(), size),
+                None => println!("  {}", path.display()),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn extract(file: &Path, dir: &Path) -> io::Result<()> {
+    for source in Source::open(file)? {
+        for path in source.files() {
+            if !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("refusing to extract {}", path.display()),
+                ));
+            }
+            let dest = dir.join(&path);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&dest, source.read(&path)?)?;
+            println!("{}", dest.display());
+        }
+    }
+    Ok(())
+}
+
+fn verify(file: &Path) -> io::Result<()> {
+    let mut failures = 0;
+    let mut unverifiable = 0;
+    for source in Source::open(file)? {
+        if !source.has_checksums() {
+            println!("{}: can't be verified, as it has no checksums", source.name());
+            unverifiable += 1;
+            continue;
+        }
+        for path in source.files() {
+            if let Err(err) = source.verify(&path) {
+                println!("{}: {}: {}", source.name(), path.display(), err);
+                failures += 1;
+            }
+        }
+    }
+    match (failures, unverifiable) {
+        (0, 0) => Ok(()),
+        (0, _) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "tar archives can't be verified",
+        )),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} assets failed verification", failures),
+        )),
+    }
+}
+
+#[cfg(feature = "signing")]
+fn sign(archive: &Path, secret_key: &Path) -> io::Result<()> {
+    let signature = sign_pack(fs::File::open(archive)?, &read_key(secret_key)?)?;
+    fs::write(signature_path(archive), signature)
+}
+
+/// Checks the signature of the archive appended to `file`, or of `file` itself
+/// if nothing was appended to it.
+#[cfg(feature = "signing")]
+fn verify_signature(file: &Path, key: &Path) -> io::Result<()> {
+    let verifier = PackVerifier::new(read_key(key)?);
+    match find_bundle(&mut fs::File::open(file)?)? {
+        Some(_) => verify_bundle(file, &verifier),
+        None => verifier.verify_file(file),
+    }
+}
+
+#[cfg(feature = "signing")]
+fn read_key(path: &Path) -> io::Result<[u8; 32]> {
+    fs::read(path)?.try_into().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} isn't a 32-byte key", path.display()),
+        )
+    })
+}
+
+/// A set of assets found in a file.
+enum Source {
+    Embedded(usize, EmbeddedAssets),
+    #[cfg(any(feature = "zip", feature = "tar"))]
+    Bundle(ArchiveAssetIo),
+    #[cfg(any(feature = "zip", feature = "tar"))]
+    Archive(ArchiveAssetIo),
+}
+
+impl Source {
+    /// Finds every set of assets in `file`.
+    fn open(file: &Path) -> io::Result<Vec<Self>> {
+        // embedded assets borrow from the binary for the rest of the program anyway
+        let binary = Box::leak(fs::read(file)?.into_boxed_slice());
+        let mut sources = EmbeddedAssets::find_in(binary)
+            .into_iter()
+            .enumerate()
+            .map(|(i, assets)| Source::Embedded(i + 1, assets))
+            .collect::<Vec<_>>();
+
+        sources.extend(Self::open_archive(file, sources.is_empty())?);
+
+        if sources.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no assets found in {}", file.display()),
+            ));
+        }
+        Ok(sources)
+    }
+
+    /// Opens the archive appended to `file`, or `file` itself if it has no embedded assets.
+    #[cfg(any(feature = "zip", feature = "tar"))]
+    fn open_archive(file: &Path, standalone: bool) -> io::Result<Option<Self>> {
+        match open_bundle(file)? {
+            Some(bundle) => Ok(Some(Source::Bundle(bundle))),
+            None if standalone => ArchiveAssetIo::open(file).map(|archive| Some(Source::Archive(archive))),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(not(any(feature = "zip", feature = "tar")))]
+    fn open_archive(_file: &Path, _standalone: bool) -> io::Result<Option<Self>> {
+        Ok(None)
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Source::Embedded(i, _) => format!("embedded #{}", i),
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Bundle(_) => "appended archive".into(),
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Archive(_) => "archive".into(),
+        }
+    }
+
+    /// Returns the path of every asset, sorted.
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = match self {
+            Source::Embedded(_, assets) => assets.assets.keys().map(|path| path.to_path_buf()).collect(),
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Bundle(archive) | Source::Archive(archive) => archive.files().map(Path::to_path_buf).collect(),
+        };
+        files.sort();
+        files
+    }
+
+    /// Returns the size of an asset, if it's known without reading it.
+    fn size(&self, path: &Path) -> Option<u64> {
+        match self {
+            Source::Embedded(_, assets) => assets.assets.get(path).map(|asset| asset.size),
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Bundle(archive) | Source::Archive(archive) => archive.size(path),
+        }
+    }
+
+    fn has_checksums(&self) -> bool {
+        match self {
+            Source::Embedded(..) => true,
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Bundle(archive) | Source::Archive(archive) => archive.has_checksums(),
+        }
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let data = match self {
+            Source::Embedded(_, assets) => match assets.assets.get(path) {
+                Some(asset) => Some(asset.contents()?.into_owned()),
+                None => None,
+            },
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Bundle(archive) | Source::Archive(archive) => archive.read_file(path)?,
+        };
+        data.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display())))
+    }
+
+    fn verify(&self, path: &Path) -> io::Result<()> {
+        match self {
+            Source::Embedded(_, assets) => match assets.assets.get(path) {
+                Some(asset) => asset.verify(),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "not found")),
+            },
+            // zip checks each file's CRC as it's read, and tar archives were ruled out above
+            #[cfg(any(feature = "zip", feature = "tar"))]
+            Source::Bundle(_) | Source::Archive(_) => self.read(path).map(drop),
+        }
+    }
+}
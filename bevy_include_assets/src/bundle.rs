@@ -0,0 +1,194 @@
+use std::fs::{self, File, OpenOptions};
+#[cfg(all(feature = "signing", any(feature = "zip", feature = "tar")))]
+use std::io::Cursor;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+#[cfg(any(feature = "zip", feature = "tar"))]
+use crate::archive::ArchiveAssetIo;
+#[cfg(feature = "signing")]
+use crate::signing::PackVerifier;
+
+/// Marks the end of an executable with an asset archive appended to it.
+const MAGIC: [u8; 8] = *b"BIABNDL1";
+
+/// Like [`MAGIC`], but the archive is followed by its 64-byte signature.
+const SIGNED_MAGIC: [u8; 8] = *b"BIABNDS1";
+
+/// Appended archives are followed by their length as a little-endian `u64`, then [`MAGIC`].
+const TRAILER_LEN: u64 = 16;
+
+const SIGNATURE_LEN: u64 = 64;
+
+/// An archive appended to an executable.
+struct Bundle {
+    start: u64,
+    len: u64,
+    #[cfg(feature = "signing")]
+    signature: Option<[u8; SIGNATURE_LEN as usize]>,
+}
+
+/// Appends the archive at `archive` to `executable`, so that the
+/// [`EmbeddedAssetsPlugin`] will serve assets out of it at runtime.
+///
+/// Any archive that was previously appended is replaced. If the archive has
+/// been signed, its signature (`<archive>.sig`) is appended along with it.
+///
+/// [`EmbeddedAssetsPlugin`]: crate::EmbeddedAssetsPlugin
+pub fn append_bundle(executable: &Path, archive: &Path) -> io::Result<()> {
+    let mut signature_path = archive.as_os_str().to_owned();
+    signature_path.push(".sig");
+    let signature = match fs::read(signature_path) {
+        Ok(signature) if signature.len() as u64 == SIGNATURE_LEN => Some(signature),
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "malformed archive signature",
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+
+    let mut executable = OpenOptions::new().read(true).write(true).open(executable)?;
+    let end = match locate(&mut executable)? {
+        Some(bundle) => bundle.start,
+        None => executable.seek(SeekFrom::End(0))?,
+    };
+    executable.set_len(end)?;
+    executable.seek(SeekFrom::Start(end))?;
+
+    let len = io::copy(&mut File::open(archive)?, &mut executable)?;
+    if let Some(signature) = &signature {
+        executable.write_all(signature)?;
+    }
+    executable.write_all(&len.to_le_bytes())?;
+    executable.write_all(if signature.is_some() { &SIGNED_MAGIC } else { &MAGIC })?;
+    executable.flush()
+}
+
+/// Returns the offset and length of the archive appended to `executable`, if any.
+pub fn find_bundle(executable: &mut (impl Read + Seek)) -> io::Result<Option<(u64, u64)>> {
+    Ok(locate(executable)?.map(|bundle| (bundle.start, bundle.len)))
+}
+
+/// Checks the signature of the archive appended to `executable`.
+#[cfg(feature = "signing")]
+pub fn verify_bundle(executable: &Path, verifier: &PackVerifier) -> io::Result<()> {
+    let mut file = File::open(executable)?;
+    let bundle = locate(&mut file)?.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no appended archive"))?;
+    file.seek(SeekFrom::Start(bundle.start))?;
+    verifier.verify(file.take(bundle.len), bundle.signature.as_ref())
+}
+
+fn locate(executable: &mut (impl Read + Seek)) -> io::Result<Option<Bundle>> {
+    let file_len = executable.seek(SeekFrom::End(0))?;
+    if file_len < TRAILER_LEN {
+        return Ok(None);
+    }
+    executable.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    let mut trailer = [0; TRAILER_LEN as usize];
+    executable.read_exact(&mut trailer)?;
+    let signature_len = match trailer[8..].try_into().unwrap() {
+        MAGIC => 0,
+        SIGNED_MAGIC => SIGNATURE_LEN,
+        _ => return Ok(None),
+    };
+
+    let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "corrupt asset bundle trailer");
+    let len = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+    let start = (file_len - TRAILER_LEN)
+        .checked_sub(signature_len)
+        .and_then(|end| end.checked_sub(len))
+        .ok_or_else(corrupt)?;
+    Ok(Some(Bundle {
+        start,
+        len,
+        #[cfg(feature = "signing")]
+        signature: match signature_len {
+            0 => None,
+            _ => {
+                let mut signature = [0; SIGNATURE_LEN as usize];
+                executable.seek(SeekFrom::Start(start + len))?;
+                executable.read_exact(&mut signature)?;
+                Some(signature)
+            }
+        },
+    }))
+}
+
+/// Opens the archive appended to `executable`, if there is one.
+#[cfg(any(feature = "zip", feature = "tar"))]
+pub fn open_bundle(executable: &Path) -> io::Result<Option<ArchiveAssetIo>> {
+    let mut file = File::open(executable)?;
+    match find_bundle(&mut file)? {
+        Some((start, len)) => ArchiveAssetIo::new(Slice::new(file, start, len)?).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Opens the archive appended to `executable`, if there is one, as long as
+/// `verifier` accepts its signature.
+///
+/// The archive is read into memory and served from there, so that it can't
+/// be swapped out after being verified.
+#[cfg(all(feature = "signing", any(feature = "zip", feature = "tar")))]
+pub fn open_verified_bundle(executable: &Path, verifier: &PackVerifier) -> io::Result<Option<ArchiveAssetIo>> {
+    let mut file = File::open(executable)?;
+    let bundle = match locate(&mut file)? {
+        Some(bundle) => bundle,
+        None => return Ok(None),
+    };
+    file.seek(SeekFrom::Start(bundle.start))?;
+    let mut data = Vec::with_capacity(bundle.len as usize);
+    file.take(bundle.len).read_to_end(&mut data)?;
+    verifier.check(executable, &data, bundle.signature.as_ref())?;
+    ArchiveAssetIo::new(Cursor::new(data)).map(Some)
+}
+
+/// A reader over part of another reader.
+#[cfg(any(feature = "zip", feature = "tar"))]
+struct Slice<R> {
+    inner: R,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+#[cfg(any(feature = "zip", feature = "tar"))]
+impl<R: Seek> Slice<R> {
+    pub fn new(mut inner: R, start: u64, len: u64) -> io::Result<Self> {
+        inner.seek(SeekFrom::Start(start))?;
+        Ok(Self {
+            inner,
+            start,
+            len,
+            pos: 0,
+        })
+    }
+}
+
+#[cfg(any(feature = "zip", feature = "tar"))]
+impl<R: Read> Read for Slice<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos).min(buf.len() as u64) as usize;
+        let read = self.inner.read(&mut buf[..remaining])?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(any(feature = "zip", feature = "tar"))]
+impl<R: Seek> Seek for Slice<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        let pos = pos.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek"))?;
+        self.inner.seek(SeekFrom::Start(self.start + pos))?;
+        self.pos = pos;
+        Ok(pos)
+    }
+}
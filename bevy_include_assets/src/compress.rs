@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+use std::path::Path;
+
+/// The most deflate can shrink data by, as a match can reference 258
+/// bytes in a little under 2 bits.
+const MAX_RATIO: u64 = 1032;
+
+/// Inflates an asset that was deflated by `include_assets!` into exactly
+/// `size` bytes, which keeps peak memory down compared to growing a buffer.
+///
+/// `size` comes from the pack, so it's checked against the most `data`
+/// could inflate to before anything is allocated.
+pub(crate) fn decompress(data: &[u8], size: u64) -> io::Result<Vec<u8>> {
+    let size = match usize::try_from(size) {
+        Ok(size) if size as u64 <= (data.len() as u64).saturating_mul(MAX_RATIO) => size,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "couldn't decompress asset: {} bytes can't inflate to {} bytes",
+                    data.len(),
+                    size
+                ),
+            ))
+        }
+    };
+    let mut inflated = vec![0; size];
+    match miniz_oxide::inflate::decompress_slice_iter_to_slice(&mut inflated, std::iter::once(data), false, true) {
+        Ok(len) if len == inflated.len() => Ok(inflated),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "couldn't decompress asset: it's smaller than expected",
+        )),
+        Err(status) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("couldn't decompress asset: {:?}", status),
+        )),
+    }
+}
+
+/// A least-recently-used cache of decompressed assets, bounded by their total size in bytes.
+pub(crate) struct DecompressionCache {
+    capacity: usize,
+    size: usize,
+    entries: VecDeque<(&'static Path, Vec<u8>)>,
+}
+
+impl DecompressionCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&mut self, path: &Path) -> Option<Vec<u8>> {
+        let index = self.entries.iter().position(|(key, _)| *key == path)?;
+        let entry = self.entries.remove(index)?;
+        let data = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(data)
+    }
+
+    pub fn insert(&mut self, path: &'static Path, data: Vec<u8>) {
+        // another thread may have decompressed the same asset in the meantime
+        if data.len() > self.capacity || self.entries.iter().any(|(key, _)| *key == path) {
+            return;
+        }
+        self.size += data.len();
+        self.entries.push_back((path, data));
+        while self.size > self.capacity {
+            let (_, evicted) = self.entries.pop_front().unwrap();
+            self.size -= evicted.len();
+        }
+    }
+}
+
+impl Debug for DecompressionCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecompressionCache")
+            .field("capacity", &self.capacity)
+            .field("size", &self.size)
+            .field("entries", &self.entries.iter().map(|(key, _)| key).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miniz_oxide::deflate::compress_to_vec;
+
+    use super::*;
+
+    #[test]
+    fn assets_inflate_to_their_size() {
+        let data = vec![7; 100_000];
+        let deflated = compress_to_vec(&data, 10);
+        assert_eq!(decompress(&deflated, data.len() as u64).unwrap(), data);
+        assert!(decompress(&deflated, data.len() as u64 - 1).is_err());
+        assert!(decompress(&deflated, data.len() as u64 + 1).is_err());
+        assert_eq!(decompress(&compress_to_vec(b"", 10), 0).unwrap(), b"");
+    }
+
+    #[test]
+    fn impossible_sizes_are_rejected() {
+        let deflated = compress_to_vec(b"bnnuy", 10);
+        let err = decompress(&deflated, u64::MAX).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(decompress(&deflated, deflated.len() as u64 * MAX_RATIO + 1).is_err());
+    }
+}
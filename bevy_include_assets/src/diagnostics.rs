@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bevy::app::App;
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, Metadata};
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy::log::warn;
+use bevy::prelude::{Local, Res, ResMut};
+
+use crate::{layered, paths};
+
+/// How many loads, bytes and misses every path served through the
+/// [`AssetServer`](bevy::asset::AssetServer) has seen.
+///
+/// The [`EmbeddedAssetsPlugin`] inserts one as a resource and also reports how
+/// much was loaded each frame as diagnostics, so that `LogDiagnosticsPlugin`
+/// prints the averages.
+///
+/// [`EmbeddedAssetsPlugin`]: crate::EmbeddedAssetsPlugin
+#[derive(Clone, Default)]
+pub struct AssetIoDiagnostics {
+    state: Arc<Mutex<DiagnosticsState>>,
+}
+
+#[derive(Default)]
+struct DiagnosticsState {
+    loads: HashMap<PathBuf, AssetLoads>,
+    misses: HashMap<PathBuf, AssetMisses>,
+}
+
+/// How often a path was loaded.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct AssetLoads {
+    pub count: u64,
+    /// The total number of bytes served, across every load.
+    pub bytes: u64,
+    /// The total time spent reading the asset, across every load.
+    pub time: Duration,
+}
+
+/// How often a path was requested that doesn't exist.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct AssetMisses {
+    pub count: u64,
+    /// The existing path closest to the missing one, if any is close.
+    pub suggestion: Option<PathBuf>,
+}
+
+impl AssetIoDiagnostics {
+    pub const LOADS: DiagnosticId = DiagnosticId::from_u128(0x6b2d1fb1_7e7c_4e57_9d77_3b8c6f0e4a01);
+    pub const BYTES: DiagnosticId = DiagnosticId::from_u128(0x6b2d1fb1_7e7c_4e57_9d77_3b8c6f0e4a02);
+    pub const MISSES: DiagnosticId = DiagnosticId::from_u128(0x6b2d1fb1_7e7c_4e57_9d77_3b8c6f0e4a03);
+    pub const LOAD_TIME: DiagnosticId = DiagnosticId::from_u128(0x6b2d1fb1_7e7c_4e57_9d77_3b8c6f0e4a04);
+
+    /// Returns the loads of every path that was loaded at least once.
+    pub fn loads(&self) -> HashMap<PathBuf, AssetLoads> {
+        self.state.lock().unwrap().loads.clone()
+    }
+
+    /// Returns the misses of every path that was requested but doesn't exist.
+    pub fn misses(&self) -> HashMap<PathBuf, AssetMisses> {
+        self.state.lock().unwrap().misses.clone()
+    }
+
+    /// Returns the loads of every path added together.
+    pub fn total(&self) -> AssetLoads {
+        let state = self.state.lock().unwrap();
+        state
+            .loads
+            .values()
+            .fold(AssetLoads::default(), |total, loads| AssetLoads {
+                count: total.count + loads.count,
+                bytes: total.bytes + loads.bytes,
+                time: total.time + loads.time,
+            })
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.loads.clear();
+        state.misses.clear();
+    }
+
+    fn record_load(&self, path: &Path, bytes: usize, time: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let loads = state.loads.entry(paths::normalize(path)).or_default();
+        loads.count += 1;
+        loads.bytes += bytes as u64;
+        loads.time += time;
+    }
+
+    /// Records a miss, returning the suggestion made the first time `path` was missed.
+    fn record_miss(&self, path: &Path, suggest: impl FnOnce() -> Option<PathBuf>) -> Option<PathBuf> {
+        let key = paths::normalize(path);
+        let previous = self
+            .state
+            .lock()
+            .unwrap()
+            .misses
+            .get(&key)
+            .map(|misses| misses.suggestion.clone());
+        // suggesting reads directories, which shouldn't block other loads
+        let suggestion = previous.unwrap_or_else(suggest);
+
+        let mut state = self.state.lock().unwrap();
+        let misses = state.misses.entry(key).or_default();
+        misses.count += 1;
+        misses.suggestion = suggestion.clone();
+        suggestion
+    }
+}
+
+impl Debug for AssetIoDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("AssetIoDiagnostics")
+            .field("loads", &state.loads)
+            .field("misses", &state.misses)
+            .finish()
+    }
+}
+
+/// An [`AssetIo`] that records every load into [`AssetIoDiagnostics`].
+pub(crate) struct DiagnosticsAssetIo {
+    inner: Box<dyn AssetIo>,
+    diagnostics: AssetIoDiagnostics,
+    /// Paths that are suggested for misses besides the ones next to them.
+    known: Vec<PathBuf>,
+}
+
+impl DiagnosticsAssetIo {
+    pub fn new(inner: Box<dyn AssetIo>, diagnostics: AssetIoDiagnostics, known: Vec<PathBuf>) -> Self {
+        Self {
+            inner,
+            diagnostics,
+            known,
+        }
+    }
+
+    /// Returns the existing file whose path is closest to `path`, if it's close enough to be a typo.
+    fn closest_match(&self, path: &Path) -> Option<PathBuf> {
+        let path = paths::normalize(path);
+        let mut candidates = self.known.clone();
+        if let Ok(siblings) = self
+            .inner
+            .read_directory(path.parent().unwrap_or_else(|| Path::new("")))
+        {
+            candidates.extend(siblings.filter(|sibling| self.inner.is_file(sibling)));
+        }
+
+        let path = path.to_string_lossy();
+        let max_distance = (path.chars().count() / 3).max(2);
+        candidates
+            .into_iter()
+            .map(|candidate| (edit_distance(&path, &candidate.to_string_lossy()), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+impl AssetIo for DiagnosticsAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = self.inner.load_path(path).await;
+            match &result {
+                Ok(data) => self.diagnostics.record_load(path, data.len(), start.elapsed()),
+                Err(err) if layered::is_not_found(err) => {
+                    match self.diagnostics.record_miss(path, || self.closest_match(path)) {
+                        Some(suggestion) => warn!(
+                            "{} doesn't exist, did you mean {}?",
+                            path.display(),
+                            suggestion.display()
+                        ),
+                        None => warn!("{} doesn't exist", path.display()),
+                    }
+                }
+                Err(_) => {}
+            }
+            result
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.inner.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        self.inner.get_metadata(path)
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        self.inner.watch_path_for_changes(path)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.inner.watch_for_changes()
+    }
+}
+
+/// Registers the diagnostics reported by [`report_diagnostics`].
+pub(crate) fn register_diagnostics(app: &mut App) {
+    app.init_resource::<Diagnostics>();
+    let mut diagnostics = app.world.resource_mut::<Diagnostics>();
+    diagnostics.add(Diagnostic::new(AssetIoDiagnostics::LOADS, "asset_loads", 20));
+    diagnostics.add(Diagnostic::new(AssetIoDiagnostics::BYTES, "asset_bytes", 20));
+    diagnostics.add(Diagnostic::new(AssetIoDiagnostics::MISSES, "asset_misses", 20));
+    diagnostics.add(Diagnostic::new(AssetIoDiagnostics::LOAD_TIME, "asset_load_time", 20).with_suffix("ms"));
+}
+
+/// Reports what was loaded and missed since the last frame, keeping the
+/// totals [`AssetIoDiagnostics`] had then in `reported`.
+pub(crate) fn report_diagnostics(
+    asset_io: Res<AssetIoDiagnostics>,
+    mut diagnostics: ResMut<Diagnostics>,
+    mut reported: Local<(AssetLoads, u64)>,
+) {
+    let total = asset_io.total();
+    let misses = asset_io
+        .state
+        .lock()
+        .unwrap()
+        .misses
+        .values()
+        .map(|misses| misses.count)
+        .sum::<u64>();
+    let (previous, previous_misses) = std::mem::replace(&mut *reported, (total, misses));
+    // the totals start over if they are cleared
+    let loads = total.count.saturating_sub(previous.count);
+    let bytes = total.bytes.saturating_sub(previous.bytes);
+    let misses = misses.saturating_sub(previous_misses);
+    let time = total.time.saturating_sub(previous.time);
+    diagnostics.add_measurement(AssetIoDiagnostics::LOADS, || loads as f64);
+    diagnostics.add_measurement(AssetIoDiagnostics::BYTES, || bytes as f64);
+    diagnostics.add_measurement(AssetIoDiagnostics::MISSES, || misses as f64);
+    diagnostics.add_measurement(AssetIoDiagnostics::LOAD_TIME, || time.as_secs_f64() * 1000.0);
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("bnnuy.png", "bnnuy.png"), 0);
+        assert_eq!(edit_distance("bnuy.png", "bnnuy.png"), 1);
+        assert_eq!(edit_distance("bnnuy.jpg", "bnnuy.png"), 2);
+        assert_eq!(edit_distance("", "ab"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+
+use crate::pack::fnv1a;
+
+/// The key `include_assets!` encrypted assets with, derived from
+/// `BEVY_INCLUDE_ASSETS_KEY` at compile time so the variable itself isn't in
+/// the binary.
+const KEY: Option<u64> = match option_env!("BEVY_INCLUDE_ASSETS_KEY") {
+    Some(key) if !key.is_empty() => Some(fnv1a(key.as_bytes())),
+    _ => None,
+};
+
+/// Decrypts an asset that was encrypted by `include_assets!`, given the hash
+/// of its original contents.
+pub(crate) fn decrypt(data: &[u8], hash: u64) -> io::Result<Vec<u8>> {
+    let mut data = data.to_vec();
+    decrypt_at(&mut data, hash, 0)?;
+    Ok(data)
+}
+
+/// Decrypts part of an asset in place, given where in the asset it starts.
+pub(crate) fn decrypt_at(data: &mut [u8], hash: u64, offset: u64) -> io::Result<()> {
+    let key = KEY.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "couldn't decrypt asset: `BEVY_INCLUDE_ASSETS_KEY` wasn't set when building",
+        )
+    })?;
+    apply_keystream(data, key, hash, offset);
+    Ok(())
+}
+
+/// XORs `data` with a SplitMix64 keystream seeded from `key` and `nonce`,
+/// starting `offset` bytes into the keystream.
+/// Must match `bevy_include_assets_macros/src/pack.rs`.
+fn apply_keystream(data: &mut [u8], key: u64, nonce: u64, offset: u64) {
+    const GAMMA: u64 = 0x9e3779b97f4a7c15;
+    // each block of 8 bytes takes one step, so blocks can be skipped
+    let mut state = (key ^ nonce.rotate_left(32)).wrapping_add((offset / 8).wrapping_mul(GAMMA));
+    let mut skip = (offset % 8) as usize;
+    let mut data = data;
+    while !data.is_empty() {
+        state = state.wrapping_add(GAMMA);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        let keystream = &z.to_le_bytes()[skip..];
+        let (chunk, rest) = data.split_at_mut(keystream.len().min(data.len()));
+        for (byte, key) in chunk.iter_mut().zip(keystream) {
+            *byte ^= key;
+        }
+        data = rest;
+        skip = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: u64 = 0x0123456789abcdef;
+    const NONCE: u64 = 0xfedcba9876543210;
+
+    #[test]
+    fn keystream_round_trips() {
+        let plain = (0..=255).collect::<Vec<u8>>();
+        let mut data = plain.clone();
+        apply_keystream(&mut data, KEY, NONCE, 0);
+        assert_ne!(data, plain);
+        apply_keystream(&mut data, KEY, NONCE, 0);
+        assert_eq!(data, plain);
+    }
+
+    #[test]
+    fn keystream_can_start_anywhere() {
+        let mut whole = vec![0; 100];
+        apply_keystream(&mut whole, KEY, NONCE, 0);
+        for start in [0, 1, 7, 8, 9, 15, 33, 99] {
+            for end in [start, start + 1, start + 7, start + 8, start + 13, 100] {
+                let end = end.min(100);
+                let mut part = vec![0; end - start];
+                apply_keystream(&mut part, KEY, NONCE, start as u64);
+                assert_eq!(part, whole[start..end], "{}..{}", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn keystream_depends_on_key_and_nonce() {
+        let mut keystreams = [(KEY, NONCE), (KEY + 1, NONCE), (KEY, NONCE + 1)].map(|(key, nonce)| {
+            let mut data = [0; 16];
+            apply_keystream(&mut data, key, nonce, 0);
+            data
+        });
+        keystreams.sort();
+        assert!(keystreams.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+}
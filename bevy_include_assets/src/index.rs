@@ -0,0 +1,171 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use bevy::asset::{AssetIoError, FileType, Metadata};
+
+use crate::paths;
+
+/// An in-memory directory tree over a set of file paths, for [`AssetIo`]s
+/// that don't have a real filesystem to ask.
+///
+/// [`AssetIo`]: bevy::asset::AssetIo
+#[derive(Clone, Debug)]
+pub(crate) struct AssetIndex {
+    dirs: HashMap<PathBuf, BTreeSet<PathBuf>>,
+    files: HashSet<PathBuf>,
+    /// Maps case-folded paths to their original spelling, if lookups are case-insensitive.
+    folded: Option<HashMap<String, PathBuf>>,
+}
+
+impl Default for AssetIndex {
+    fn default() -> Self {
+        // the root directory exists even if there are no files
+        Self {
+            dirs: HashMap::from([(PathBuf::new(), BTreeSet::new())]),
+            files: HashSet::new(),
+            folded: None,
+        }
+    }
+}
+
+impl AssetIndex {
+    pub fn new<P: AsRef<Path>>(files: impl IntoIterator<Item = P>) -> Self {
+        let mut index = Self::default();
+        for file in files {
+            index.insert(file.as_ref());
+        }
+        index
+    }
+
+    /// Adds a file and every directory above it to the tree.
+    pub fn insert(&mut self, path: &Path) {
+        self.files.insert(path.to_path_buf());
+        self.fold(path);
+        let mut child = path;
+        while let Some(parent) = child.parent() {
+            self.dirs
+                .entry(parent.to_path_buf())
+                .or_default()
+                .insert(child.to_path_buf());
+            self.fold(parent);
+            child = parent;
+        }
+    }
+
+    /// Removes a file, along with every directory above it that is left empty.
+    pub fn remove(&mut self, path: &Path) {
+        if !self.files.remove(path) {
+            return;
+        }
+        self.unfold(path);
+        let mut child = path;
+        while let Some(parent) = child.parent() {
+            let dir = self.dirs.get_mut(parent).unwrap();
+            dir.remove(child);
+            // the root directory always exists
+            if !dir.is_empty() || parent.as_os_str().is_empty() {
+                break;
+            }
+            self.dirs.remove(parent);
+            self.unfold(parent);
+            child = parent;
+        }
+    }
+
+    /// Returns every file in the tree, in no particular order.
+    #[cfg(any(feature = "zip", feature = "tar"))]
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(PathBuf::as_path)
+    }
+
+    fn fold(&mut self, path: &Path) {
+        if let Some(folded) = &mut self.folded {
+            folded.insert(paths::fold(path), path.to_path_buf());
+        }
+    }
+
+    fn unfold(&mut self, path: &Path) {
+        if let Some(folded) = &mut self.folded {
+            folded.remove(&paths::fold(path));
+        }
+    }
+
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.folded = case_insensitive.then(|| {
+            let keys = self.dirs.keys().chain(&self.files);
+            keys.map(|key| (paths::fold(key), key.clone())).collect()
+        });
+    }
+
+    /// Converts a requested path into the key it is indexed under.
+    pub fn key(&self, path: &Path) -> PathBuf {
+        let path = paths::normalize(path);
+        match self.folded.as_ref().and_then(|folded| folded.get(&paths::fold(&path))) {
+            Some(key) => key.clone(),
+            None => path,
+        }
+    }
+
+    pub fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        match self.dirs.get(&self.key(path)) {
+            Some(dir) => Ok(Box::new(dir.clone().into_iter())),
+            None => Err(AssetIoError::NotFound(path.to_path_buf())),
+        }
+    }
+
+    pub fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        let key = self.key(path);
+        if self.dirs.contains_key(&key) {
+            Ok(Metadata::new(FileType::Directory))
+        } else if self.files.contains(&key) {
+            Ok(Metadata::new(FileType::File))
+        } else {
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn children(index: &AssetIndex, dir: &str) -> Vec<PathBuf> {
+        index.read_directory(Path::new(dir)).unwrap().collect()
+    }
+
+    #[test]
+    fn builds_directories_from_files() {
+        let index = AssetIndex::new(["bnnuy.png", "fonts/bold.ttf", "fonts/cjk/regular.ttf"]);
+        assert_eq!(children(&index, ""), [Path::new("bnnuy.png"), Path::new("fonts")]);
+        assert_eq!(
+            children(&index, "./fonts/"),
+            [Path::new("fonts/bold.ttf"), Path::new("fonts/cjk")]
+        );
+        assert!(index.get_metadata(Path::new("fonts/cjk")).unwrap().is_dir());
+        assert!(index.get_metadata(Path::new("fonts/bold.ttf")).unwrap().is_file());
+        assert!(index.get_metadata(Path::new("fonts/italic.ttf")).is_err());
+    }
+
+    #[test]
+    fn removing_files_prunes_empty_directories() {
+        let mut index = AssetIndex::new(["bnnuy.png", "fonts/cjk/regular.ttf"]);
+        index.remove(Path::new("fonts/cjk/regular.ttf"));
+        assert_eq!(children(&index, ""), [Path::new("bnnuy.png")]);
+        assert!(index.get_metadata(Path::new("fonts")).is_err());
+
+        index.remove(Path::new("bnnuy.png"));
+        assert!(children(&index, "").is_empty());
+    }
+
+    #[test]
+    fn case_insensitive_keys_keep_their_spelling() {
+        let mut index = AssetIndex::new(["Fonts/Bold.ttf"]);
+        assert_eq!(index.key(Path::new("fonts/bold.TTF")), Path::new("fonts/bold.TTF"));
+        index.set_case_insensitive(true);
+        assert_eq!(index.key(Path::new("fonts/bold.TTF")), Path::new("Fonts/Bold.ttf"));
+        assert!(index.get_metadata(Path::new("FONTS")).unwrap().is_dir());
+
+        index.insert(Path::new("Music/Theme.ogg"));
+        assert_eq!(index.key(Path::new("music/theme.ogg")), Path::new("Music/Theme.ogg"));
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, Metadata};
+
+/// An [`AssetIo`] that searches a list of other [`AssetIo`]s in order.
+///
+/// Each layer is only consulted if every layer before it reported that the
+/// path doesn't exist; any other error is returned immediately.
+pub struct LayeredAssetIo {
+    layers: Vec<Box<dyn AssetIo>>,
+}
+
+impl LayeredAssetIo {
+    pub fn new(layers: Vec<Box<dyn AssetIo>>) -> Self {
+        Self { layers }
+    }
+}
+
+impl AssetIo for LayeredAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            for layer in &self.layers {
+                match layer.load_path(path).await {
+                    Err(err) if is_not_found(&err) => continue,
+                    result => return result,
+                }
+            }
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let mut found = false;
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for layer in &self.layers {
+            match layer.read_directory(path) {
+                Ok(dir) => {
+                    found = true;
+                    entries.extend(dir.filter(|entry| seen.insert(entry.clone())));
+                }
+                Err(err) if is_not_found(&err) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        if found {
+            Ok(Box::new(entries.into_iter()))
+        } else {
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        }
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        for layer in &self.layers {
+            match layer.get_metadata(path) {
+                Err(err) if is_not_found(&err) => continue,
+                result => return result,
+            }
+        }
+        Err(AssetIoError::NotFound(path.to_path_buf()))
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        // the path usually only exists in some of the layers
+        let mut result = Err(AssetIoError::PathWatchError(path.to_path_buf()));
+        for layer in &self.layers {
+            if let Err(err) = layer.watch_path_for_changes(path) {
+                if result.is_err() {
+                    result = Err(err);
+                }
+            } else {
+                result = Ok(());
+            }
+        }
+        result
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        for layer in &self.layers {
+            layer.watch_for_changes()?;
+        }
+        Ok(())
+    }
+}
+
+/// `FileAssetIo::read_directory` reports missing directories as plain IO errors.
+pub(crate) fn is_not_found(err: &AssetIoError) -> bool {
+    match err {
+        AssetIoError::NotFound(_) => true,
+        AssetIoError::Io(err) => err.kind() == io::ErrorKind::NotFound,
+        _ => false,
+    }
+}
@@ -3,64 +3,332 @@
 //! A Bevy plugin that directly packages assets within the binary.
 //! Adapted from Arc-blroth/TrustworthyDolphin's [assets.rs][1].
 //!
+//! With the `compress` feature enabled, assets are deflated at compile time
+//! and inflated again whenever they are loaded.
+//!
+//! With the `encrypt` feature enabled, assets are also encrypted at compile
+//! time with the key in `BEVY_INCLUDE_ASSETS_KEY`, and decrypted when loaded.
+//!
+//! Build scripts can embed the assets listed in a RON or TOML manifest with
+//! the `bevy_include_assets_build` crate, which doesn't depend on Bevy.
+//!
 //! [1]: https://github.com/Arc-blroth/TrustworthyDolphin/blob/main/src/assets.rs
 
+mod alias;
+#[cfg(any(feature = "zip", feature = "tar"))]
+mod archive;
+mod bundle;
+#[cfg(feature = "compress")]
+mod compress;
+mod diagnostics;
+#[cfg(feature = "encrypt")]
+mod encrypt;
+mod index;
+mod layered;
+mod manifest;
+mod memory;
+mod mount;
+mod pack;
+mod paths;
+mod reader;
+#[cfg(feature = "signing")]
+mod signing;
+mod source;
+mod table;
+mod variant;
+
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "compress")]
+use std::sync::{Arc, Mutex};
 
-use bevy::app::App;
-use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, FileType, Metadata};
-use bevy::prelude::{AssetServer, Plugin};
+use bevy::app::{App, PluginGroupBuilder};
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+use bevy::asset::FileAssetIo;
+use bevy::asset::{
+    create_platform_default_asset_io, AssetIo, AssetIoError, AssetPlugin, AssetServerSettings, BoxedFuture, Metadata,
+};
+#[cfg(any(feature = "zip", feature = "tar"))]
+use bevy::log::error;
+use bevy::log::{info, warn};
+use bevy::prelude::{AssetServer, CoreStage, Plugin};
+pub use bevy_include_assets_macros::{include_asset_dir, include_asset_table, include_assets};
 
+use crate::alias::{AliasAssetIo, AssetAliases};
+#[cfg(any(feature = "zip", feature = "tar"))]
+pub use crate::archive::{ArchiveAssetIo, ArchiveAssetsPlugin, ArchiveReader};
+#[cfg(any(feature = "zip", feature = "tar"))]
+pub use crate::bundle::open_bundle;
+#[cfg(all(feature = "signing", any(feature = "zip", feature = "tar")))]
+pub use crate::bundle::open_verified_bundle;
+#[cfg(feature = "signing")]
+pub use crate::bundle::verify_bundle;
+pub use crate::bundle::{append_bundle, find_bundle};
+#[cfg(feature = "compress")]
+use crate::compress::DecompressionCache;
+use crate::diagnostics::DiagnosticsAssetIo;
+pub use crate::diagnostics::{AssetIoDiagnostics, AssetLoads, AssetMisses};
+use crate::index::AssetIndex;
+pub use crate::layered::LayeredAssetIo;
+use crate::manifest::CheckedAssetIo;
+pub use crate::manifest::{AssetInfo, AssetManifest};
+pub use crate::memory::MemoryAssets;
+pub use crate::mount::AssetMounts;
+pub use crate::reader::EmbeddedAssetReader;
+#[cfg(feature = "signing")]
+pub use crate::signing::{public_key, sign_pack, signature_path, PackVerifier, SignaturePolicy};
+use crate::source::{PolledSources, SourceAssetIo};
+pub use crate::table::{load_asset_table, AssetTable, AssetTablePlugin};
+pub use crate::variant::AssetVariants;
+use crate::variant::VariantAssetIo;
+
+/// Assets embedded into the binary by [`include_assets!`] or [`include_asset_dir!`].
 #[derive(Clone, Default, Debug)]
-struct EmbeddedAssetIo {
-    dirs: HashMap<&'static Path, Vec<PathBuf>>,
-    assets: HashMap<&'static Path, &'static [u8]>,
+pub struct EmbeddedAssets {
+    /// Each asset, keyed by its path relative to the asset directory.
+    pub assets: HashMap<&'static Path, EmbeddedAsset>,
+    /// The absolute path of the directory the assets were embedded from,
+    /// which is only kept in debug builds.
+    pub source_dir: Option<&'static Path>,
+    /// The file each asset was embedded from, relative to `source_dir`,
+    /// which differs from its key if it was renamed. Assets a processor
+    /// converted to another format are left out, since the file can't stand
+    /// in for them. Also only kept in debug builds.
+    pub sources: HashMap<&'static Path, &'static Path>,
+}
+
+impl EmbeddedAssets {
+    /// Reads the asset pack generated by [`include_assets!`], returning `None` if it is corrupt.
+    pub fn from_pack(pack: &'static [u8]) -> Option<Self> {
+        pack::parse(pack)
+    }
+
+    /// Finds the assets embedded into another binary, like a built game,
+    /// with one entry per [`include_assets!`] invocation.
+    pub fn find_in(binary: &'static [u8]) -> Vec<Self> {
+        pack::find(binary)
+    }
+}
+
+impl From<HashMap<&'static Path, &'static [u8]>> for EmbeddedAssets {
+    fn from(assets: HashMap<&'static Path, &'static [u8]>) -> Self {
+        Self {
+            assets: assets
+                .into_iter()
+                .map(|(path, data)| (path, EmbeddedAsset::new(data)))
+                .collect(),
+            ..Self::default()
+        }
+    }
+}
+
+/// A single embedded asset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmbeddedAsset {
+    /// The asset's contents as stored in the binary.
+    pub data: &'static [u8],
+    /// Whether `data` is deflated.
+    pub deflated: bool,
+    /// Whether `data` is encrypted, which happens after deflating it.
+    pub encrypted: bool,
+    /// The size of the original file in bytes.
+    pub size: u64,
+    /// The 64-bit FNV-1a hash of the original file.
+    pub hash: u64,
+}
+
+impl EmbeddedAsset {
+    pub fn new(data: &'static [u8]) -> Self {
+        Self {
+            data,
+            deflated: false,
+            encrypted: false,
+            size: data.len() as u64,
+            hash: pack::fnv1a(data),
+        }
+    }
+
+    /// Returns the asset's original contents, decrypting and inflating them if necessary.
+    pub fn contents(&self) -> io::Result<Cow<'static, [u8]>> {
+        let data = self.decrypted()?;
+        if !self.deflated {
+            return Ok(data);
+        }
+        #[cfg(feature = "compress")]
+        return compress::decompress(&data, self.size).map(Cow::Owned);
+        #[cfg(not(feature = "compress"))]
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "deflated assets need the `compress` feature",
+        ))
+    }
+
+    /// Returns a reader over the asset's original contents, which doesn't copy
+    /// them out of the binary unless they're deflated.
+    pub fn reader(&self) -> io::Result<EmbeddedAssetReader> {
+        if self.deflated {
+            return self.contents().map(EmbeddedAssetReader::new);
+        }
+        #[cfg(feature = "encrypt")]
+        if self.encrypted {
+            return Ok(EmbeddedAssetReader::encrypted(self.data, self.hash));
+        }
+        self.decrypted().map(EmbeddedAssetReader::new)
+    }
+
+    /// Returns the bytes of the asset's original contents in `range`,
+    /// borrowing them from the binary if possible.
+    pub fn read_range(&self, range: Range<u64>) -> io::Result<Cow<'static, [u8]>> {
+        self.reader()?.read_range(range)
+    }
+
+    fn decrypted(&self) -> io::Result<Cow<'static, [u8]>> {
+        if !self.encrypted {
+            return Ok(Cow::Borrowed(self.data));
+        }
+        #[cfg(feature = "encrypt")]
+        return encrypt::decrypt(self.data, self.hash).map(Cow::Owned);
+        #[cfg(not(feature = "encrypt"))]
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "encrypted assets need the `encrypt` feature",
+        ))
+    }
+
+    /// Returns the size and hash of the original file.
+    pub fn info(&self) -> AssetInfo {
+        AssetInfo {
+            size: self.size,
+            hash: self.hash,
+        }
+    }
+
+    /// Checks that the asset's contents match the size and hash it was embedded with.
+    pub fn verify(&self) -> io::Result<()> {
+        if AssetInfo::of(&self.contents()?) != self.info() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "contents don't match their hash",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An [`AssetIo`] that serves [`EmbeddedAssets`].
+///
+/// The [`EmbeddedAssetsPlugin`] inserts one as a resource, so that systems can
+/// stream assets with [`reader`](Self::reader), since [`AssetIo::load_path`]
+/// has to return a copy of the whole asset. Asset loaders can't access
+/// resources, so they need their own clone from [`EmbeddedAssetsPlugin::embedded`].
+#[derive(Clone, Default, Debug)]
+pub struct EmbeddedAssetIo {
+    index: AssetIndex,
+    assets: HashMap<&'static Path, EmbeddedAsset>,
+    #[cfg(feature = "compress")]
+    cache: Option<Arc<Mutex<DecompressionCache>>>,
 }
 
 impl EmbeddedAssetIo {
-    pub fn new(assets: HashMap<&'static Path, &'static [u8]>) -> Self {
-        let mut dirs = HashMap::new();
-        for asset in &assets {
-            if let Some(parent) = asset.0.parent() {
-                let directory = match dirs.get_mut(parent) {
-                    Some(directory) => directory,
-                    None => {
-                        dirs.insert(parent, Vec::new());
-                        dirs.get_mut(parent).unwrap()
-                    }
-                };
-                directory.push(asset.0.to_path_buf());
+    /// Serves `assets`, whose paths are normalized like the paths they're
+    /// loaded with, so `./ui\title.png` is served as `ui/title.png`. If two
+    /// paths normalize to the same one, the one that was already normalized wins.
+    pub fn new(assets: HashMap<&'static Path, EmbeddedAsset>) -> Self {
+        let mut normalized = HashMap::with_capacity(assets.len());
+        for (path, asset) in assets {
+            let key = paths::normalize(path);
+            if key == path {
+                normalized.insert(path, asset);
+            } else {
+                // keys have to live as long as the assets, and this only happens once
+                normalized.entry(&*Box::leak(key.into_boxed_path())).or_insert(asset);
             }
         }
+        let assets = normalized;
+        Self {
+            index: AssetIndex::new(assets.keys()),
+            assets,
+            #[cfg(feature = "compress")]
+            cache: None,
+        }
+    }
 
-        Self { dirs, assets }
+    /// Returns the asset at `path`, if it was embedded.
+    pub fn asset(&self, path: &Path) -> Option<&EmbeddedAsset> {
+        self.assets.get(self.index.key(path).as_path())
     }
+
+    /// Returns a reader over the asset at `path`, for streaming large assets
+    /// like audio and video instead of loading them whole.
+    ///
+    /// Unlike the [`AssetServer`], this only knows about embedded assets, and
+    /// `path` is what the asset was embedded as: the plugin's mount point,
+    /// aliases and variants don't apply, so a loader serving `shared/intro.webm`
+    /// has to strip the mount point before asking for `intro.webm`.
+    pub fn reader(&self, path: &Path) -> Result<EmbeddedAssetReader, AssetIoError> {
+        let asset = self
+            .asset(path)
+            .ok_or_else(|| AssetIoError::NotFound(path.to_path_buf()))?;
+        Ok(asset.reader().map_err(|err| read_error(path, err))?)
+    }
+
+    /// Returns the size and hash of every embedded asset.
+    pub fn manifest(&self) -> AssetManifest {
+        AssetManifest::new(
+            self.assets
+                .iter()
+                .map(|(path, asset)| (path.to_path_buf(), asset.info())),
+        )
+    }
+
+    #[cfg(not(feature = "compress"))]
+    fn read(&self, path: &'static Path, asset: &EmbeddedAsset) -> Result<Vec<u8>, AssetIoError> {
+        Ok(contents(path, asset)?.into_owned())
+    }
+
+    #[cfg(feature = "compress")]
+    fn read(&self, path: &'static Path, asset: &EmbeddedAsset) -> Result<Vec<u8>, AssetIoError> {
+        let cache = match &self.cache {
+            Some(cache) if asset.deflated => cache,
+            _ => return Ok(contents(path, asset)?.into_owned()),
+        };
+        if let Some(data) = cache.lock().unwrap().get(path) {
+            return Ok(data);
+        }
+        let data = contents(path, asset)?.into_owned();
+        cache.lock().unwrap().insert(path, data.clone());
+        Ok(data)
+    }
+}
+
+/// Like [`EmbeddedAsset::contents`], but says which asset couldn't be read.
+fn contents(path: &Path, asset: &EmbeddedAsset) -> io::Result<Cow<'static, [u8]>> {
+    asset.contents().map_err(|err| read_error(path, err))
+}
+
+fn read_error(path: &Path, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("couldn't read {}: {}", path.display(), err))
 }
 
 impl AssetIo for EmbeddedAssetIo {
     fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
         Box::pin(async move {
-            match self.assets.get(path) {
-                Some(asset) => Ok((*asset).into()),
+            match self.assets.get_key_value(self.index.key(path).as_path()) {
+                Some((&path, asset)) => self.read(path, asset),
                 None => Err(AssetIoError::NotFound(path.to_path_buf())),
             }
         })
     }
 
     fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
-        match self.dirs.get(path) {
-            Some(dir) => Ok(Box::new(dir.clone().into_iter())),
-            None => Err(AssetIoError::NotFound(path.to_path_buf())),
-        }
+        self.index.read_directory(path)
     }
 
     fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
-        Ok(Metadata::new(if self.dirs.contains_key(path) {
-            FileType::Directory
-        } else {
-            FileType::File
-        }))
+        self.index.get_metadata(path)
     }
 
     fn watch_path_for_changes(&self, _path: &Path) -> Result<(), AssetIoError> {
@@ -72,19 +340,321 @@ impl AssetIo for EmbeddedAssetIo {
     }
 }
 
+/// Where the [`EmbeddedAssetsPlugin`] looks for assets.
+///
+/// With hot reloading enabled, the asset folder is polled for changes.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Precedence {
+    /// Only embedded assets are available.
+    #[default]
+    EmbeddedOnly,
+    /// Embedded assets are used if present, otherwise the asset folder is searched.
+    EmbeddedFirst,
+    /// Files in the asset folder override embedded assets, and a warning is
+    /// logged whenever one differs from the version that was embedded.
+    FilesystemFirst,
+}
+
+/// Whether the [`EmbeddedAssetsPlugin`] serves embedded assets at all.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum EmbeddingMode {
+    /// Embedded assets are served according to the [`Precedence`].
+    Embedded,
+    /// Embedded assets are ignored and everything is loaded from the asset
+    /// folder, which is polled for changes if hot reloading is enabled.
+    Disk,
+    /// Embedded assets are served in release builds, or whenever the `embed`
+    /// feature is enabled, and ignored otherwise.
+    #[default]
+    Auto,
+}
+
+impl EmbeddingMode {
+    pub fn embeds(self) -> bool {
+        match self {
+            Self::Embedded => true,
+            Self::Disk => false,
+            Self::Auto => cfg!(any(not(debug_assertions), feature = "embed")),
+        }
+    }
+}
+
+/// Serves embedded assets through the [`AssetServer`].
+///
+/// If `AssetServerSettings::watch_for_changes` is enabled in a debug build and
+/// the directory the assets were embedded from still exists, the files each asset was
+/// embedded from are served instead and hot reloaded whenever they change, with the
+/// embedded copies as a fallback. Files that weren't embedded aren't served.
+///
+/// The size and hash of each embedded asset is available as an [`AssetManifest`] resource,
+/// more assets can be mounted at runtime through the [`AssetMounts`] resource,
+/// generated assets can be added through the [`MemoryAssets`] resource,
+/// localized or high resolution variants are picked through the [`AssetVariants`] resource,
+/// and every load is recorded in the [`AssetIoDiagnostics`] resource.
+/// These are available whether or not assets are embedded, as set by the [`EmbeddingMode`].
+///
+/// The plugin has to be added before Bevy's `AssetPlugin`:
+///
+/// ```ignore
+/// App::new().add_plugins_with(DefaultPlugins, |group| EmbeddedAssetsPlugin::new(assets).add_to(group))
+/// ```
 #[derive(Default, Debug)]
-pub struct EmbeddedAssetsPlugin(EmbeddedAssetIo);
+pub struct EmbeddedAssetsPlugin {
+    io: EmbeddedAssetIo,
+    source_dir: Option<&'static Path>,
+    sources: HashMap<&'static Path, &'static Path>,
+    mode: EmbeddingMode,
+    precedence: Precedence,
+    mount_point: PathBuf,
+    aliases: Vec<(PathBuf, PathBuf)>,
+    variants: AssetVariants,
+    #[cfg(feature = "signing")]
+    verifier: Option<PackVerifier>,
+}
 
 impl EmbeddedAssetsPlugin {
-    pub fn new(assets: HashMap<&'static ::std::path::Path, &'static [u8]>) -> Self {
-        Self(EmbeddedAssetIo::new(assets))
+    pub fn new(assets: impl Into<EmbeddedAssets>) -> Self {
+        let EmbeddedAssets {
+            assets,
+            source_dir,
+            sources,
+        } = assets.into();
+        Self {
+            io: EmbeddedAssetIo::new(assets),
+            source_dir,
+            sources,
+            mode: EmbeddingMode::default(),
+            precedence: Precedence::default(),
+            mount_point: PathBuf::new(),
+            aliases: Vec::new(),
+            variants: AssetVariants::default(),
+            #[cfg(feature = "signing")]
+            verifier: None,
+        }
+    }
+
+    /// Sets whether embedded assets are served, which by default they only are in release builds.
+    pub fn with_mode(mut self, mode: EmbeddingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets whether the platform's default asset folder (as configured by
+    /// `AssetServerSettings::asset_folder`) is also searched for assets.
+    pub fn with_precedence(mut self, precedence: Precedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Serves the assets under `prefix`, so that e.g. `bnnuy.png` is loaded
+    /// as `shared/bnnuy.png`. This also applies to the asset folder.
+    pub fn with_mount_point(mut self, prefix: impl AsRef<Path>) -> Self {
+        self.mount_point = paths::normalize(prefix.as_ref());
+        self
+    }
+
+    /// Serves the asset or directory at `target` as `alias` too, so that
+    /// games sharing an asset tree can keep their own paths.
+    ///
+    /// ```ignore
+    /// EmbeddedAssetsPlugin::new(assets).with_alias("player.png", "skins/default/bnnuy.png")
+    /// ```
+    ///
+    /// Aliases apply to every asset the [`AssetServer`] loads, including
+    /// mounted and generated ones, and point at paths after the mount point.
+    pub fn with_alias(mut self, alias: impl AsRef<Path>, target: impl AsRef<Path>) -> Self {
+        let alias = paths::normalize(alias.as_ref());
+        self.aliases.retain(|(existing, _)| *existing != alias);
+        self.aliases.push((alias, paths::normalize(target.as_ref())));
+        self
+    }
+
+    /// Sets the [`AssetVariants`] used from the start, so that assets loaded
+    /// during startup don't have to be reloaded.
+    pub fn with_variants(mut self, variants: AssetVariants) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// Sets whether asset paths are matched case-insensitively, like they
+    /// would be on a case-insensitive filesystem.
+    pub fn with_case_insensitive_paths(mut self, case_insensitive: bool) -> Self {
+        self.io.index.set_case_insensitive(case_insensitive);
+        self
+    }
+
+    /// Requires archives appended to the executable by [`append_bundle`] to be
+    /// signed before they are allowed to override embedded assets.
+    ///
+    /// Loose files can't be signed, so [`Precedence::FilesystemFirst`] then
+    /// acts like [`Precedence::EmbeddedFirst`], and embedded assets aren't hot
+    /// reloaded from the directory they were embedded from. Archives mounted
+    /// at runtime can be checked with [`AssetMounts::mount_verified`].
+    #[cfg(feature = "signing")]
+    pub fn with_verifier(mut self, verifier: PackVerifier) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    /// Keeps up to `capacity` bytes of recently loaded assets around decompressed,
+    /// so that reloading them doesn't decompress them again.
+    #[cfg(feature = "compress")]
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.io.cache = Some(Arc::new(Mutex::new(DecompressionCache::new(capacity))));
+        self
+    }
+
+    /// Returns the embedded assets, for custom asset loaders to stream them
+    /// with [`EmbeddedAssetIo::reader`].
+    ///
+    /// ```ignore
+    /// let plugin = EmbeddedAssetsPlugin::new(assets);
+    /// let embedded = plugin.embedded().clone();
+    /// App::new()
+    ///     .add_plugins_with(DefaultPlugins, |group| plugin.add_to(group))
+    ///     .add_asset_loader(VideoLoader { embedded });
+    /// ```
+    pub fn embedded(&self) -> &EmbeddedAssetIo {
+        &self.io
+    }
+
+    /// Adds this plugin to a plugin group like `DefaultPlugins`, before the
+    /// `AssetPlugin` it replaces the asset server of.
+    pub fn add_to(self, group: &mut PluginGroupBuilder) -> &mut PluginGroupBuilder {
+        group.add_before::<AssetPlugin, _>(self)
+    }
+
+    /// Combines the embedded assets with the asset folder according to the precedence.
+    fn embedded_io(
+        &self,
+        app: &mut App,
+        manifest: &AssetManifest,
+        sources: &mut Vec<SourceAssetIo>,
+    ) -> Box<dyn AssetIo> {
+        let checked = |io| -> Box<dyn AssetIo> { Box::new(CheckedAssetIo::new(io, manifest.clone())) };
+
+        let embedded: Box<dyn AssetIo> = Box::new(self.io.clone());
+        #[cfg(any(feature = "zip", feature = "tar"))]
+        let embedded = match self.appended_bundle() {
+            Some(bundle) => Box::new(LayeredAssetIo::new(vec![checked(Box::new(bundle)), embedded])),
+            None => embedded,
+        };
+        let precedence = match self.precedence {
+            Precedence::FilesystemFirst if self.requires_signatures() => {
+                warn!("Not letting the asset folder override embedded assets, as it can't be verified");
+                Precedence::EmbeddedFirst
+            }
+            precedence => precedence,
+        };
+        match precedence {
+            Precedence::EmbeddedOnly => embedded,
+            Precedence::EmbeddedFirst => Box::new(LayeredAssetIo::new(vec![
+                embedded,
+                asset_folder_io(app, &self.mount_point, sources),
+            ])),
+            Precedence::FilesystemFirst => Box::new(LayeredAssetIo::new(vec![
+                checked(asset_folder_io(app, &self.mount_point, sources)),
+                embedded,
+            ])),
+        }
+    }
+
+    /// Opens the asset archive appended to the running executable by [`append_bundle`], if any.
+    #[cfg(any(feature = "zip", feature = "tar"))]
+    fn appended_bundle(&self) -> Option<ArchiveAssetIo> {
+        let executable = std::env::current_exe().ok()?;
+        #[cfg(feature = "signing")]
+        let bundle = match &self.verifier {
+            Some(verifier) => open_verified_bundle(&executable, verifier),
+            None => open_bundle(&executable),
+        };
+        #[cfg(not(feature = "signing"))]
+        let bundle = open_bundle(&executable);
+        bundle.unwrap_or_else(|err| {
+            error!("Couldn't open assets appended to {}: {}", executable.display(), err);
+            None
+        })
+    }
+
+    /// Whether everything overriding embedded assets has to be signed, which rules out loose files.
+    fn requires_signatures(&self) -> bool {
+        #[cfg(feature = "signing")]
+        let required = self.verifier.is_some();
+        #[cfg(not(feature = "signing"))]
+        let required = false;
+        required
     }
 }
 
 impl Plugin for EmbeddedAssetsPlugin {
     fn build(&self, app: &mut App) {
-        let asset_server = AssetServer::with_boxed_io(Box::new(self.0.clone()));
-        app.insert_resource(asset_server);
+        let manifest = self.io.manifest();
+        let embeds = self.mode.embeds();
+        let mut sources = Vec::new();
+        let mut asset_io = if embeds {
+            self.embedded_io(app, &manifest, &mut sources)
+        } else {
+            asset_folder_io(app, &self.mount_point, &mut sources)
+        };
+
+        if !self.mount_point.as_os_str().is_empty() {
+            let mount_point = AssetMounts::default();
+            mount_point.mount_boxed(&self.mount_point, asset_io);
+            asset_io = Box::new(mount_point);
+        }
+
+        // on the web, `is_dir` is always false
+        let source_dir = self
+            .source_dir
+            .filter(|dir| embeds && !self.requires_signatures() && watch_for_changes(app) && dir.is_dir());
+        if let Some(source_dir) = source_dir {
+            info!("Hot reloading embedded assets from {}", source_dir.display());
+            // only what was embedded is served, so that debug builds see the same assets as release builds
+            let files = self
+                .sources
+                .iter()
+                .map(|(key, source)| (key.to_path_buf(), source.to_path_buf()))
+                .collect();
+            let source = SourceAssetIo::new(source_dir, &self.mount_point).with_files(files);
+            asset_io = Box::new(LayeredAssetIo::new(vec![Box::new(source.clone()), asset_io]));
+            sources.push(source);
+        }
+
+        let mounts = AssetMounts::default();
+        asset_io = Box::new(LayeredAssetIo::new(vec![Box::new(mounts.clone()), asset_io]));
+
+        if !sources.is_empty() {
+            app.insert_resource(PolledSources(sources))
+                .add_system_to_stage(CoreStage::PreUpdate, source::reload_changed_sources);
+        }
+
+        let memory = MemoryAssets::default();
+        asset_io = Box::new(LayeredAssetIo::new(vec![Box::new(memory.clone()), asset_io]));
+        let aliases = AssetAliases::default();
+        asset_io = Box::new(VariantAssetIo::new(asset_io, self.variants.clone(), aliases.clone()));
+        // added even without aliases, as it records every path the asset server loads
+        asset_io = Box::new(AliasAssetIo::new(asset_io, &self.aliases, aliases.clone()));
+
+        let diagnostics = AssetIoDiagnostics::default();
+        let known = manifest
+            .iter()
+            .map(|(path, _)| self.mount_point.join(path))
+            .chain(self.aliases.iter().map(|(alias, _)| alias.clone()))
+            .collect();
+        asset_io = Box::new(DiagnosticsAssetIo::new(asset_io, diagnostics.clone(), known));
+        diagnostics::register_diagnostics(app);
+
+        app.insert_resource(manifest)
+            .insert_resource(self.io.clone())
+            .insert_resource(mounts)
+            .insert_resource(memory)
+            .insert_resource(self.variants.clone())
+            .insert_resource(aliases)
+            .insert_resource(diagnostics)
+            .add_system_to_stage(CoreStage::PostUpdate, diagnostics::report_diagnostics)
+            .add_system_to_stage(CoreStage::PreUpdate, memory::reload_changed_memory_assets)
+            .add_system_to_stage(CoreStage::PreUpdate, variant::reload_changed_variants)
+            .insert_resource(AssetServer::with_boxed_io(asset_io));
     }
 
     fn name(&self) -> &str {
@@ -92,13 +662,55 @@ impl Plugin for EmbeddedAssetsPlugin {
     }
 }
 
-#[macro_export]
-macro_rules! include_assets {
-    ($path:literal / $($asset:literal),*$(,)?) => {{
-        let mut assets = ::std::collections::HashMap::<&'static ::std::path::Path, &'static [u8]>::new();
-        $(
-            assets.insert($asset.as_ref(), &::std::include_bytes!(::std::concat!($path, "/", $asset))[..]);
-        )*
-        assets
-    }}
+/// Reads the platform's default asset folder. When hot reloading is enabled,
+/// the folder is polled for changes instead, as Bevy's own change detection
+/// stops working once its `FileAssetIo` is wrapped in other [`AssetIo`]s.
+fn asset_folder_io(app: &mut App, mount_point: &Path, sources: &mut Vec<SourceAssetIo>) -> Box<dyn AssetIo> {
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+    if let Some(settings) = app
+        .world
+        .get_resource::<AssetServerSettings>()
+        .filter(|settings| settings.watch_for_changes)
+    {
+        let folder = FileAssetIo::get_base_path().join(&settings.asset_folder);
+        let source = SourceAssetIo::new(folder, "").mounted_at(mount_point);
+        sources.push(source.clone());
+        return Box::new(source);
+    }
+    // on the web and Android, the asset folder isn't on the filesystem to poll
+    #[cfg(any(target_arch = "wasm32", target_os = "android"))]
+    let _ = (mount_point, sources);
+    create_platform_default_asset_io(app)
+}
+
+fn watch_for_changes(app: &App) -> bool {
+    app.world
+        .get_resource::<AssetServerSettings>()
+        .map_or(false, |settings| settings.watch_for_changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_paths_are_normalized() {
+        let title = EmbeddedAsset::new(b"title");
+        let other = EmbeddedAsset::new(b"other");
+        let io = EmbeddedAssetIo::new(HashMap::from([
+            (Path::new("./bnnuy.png"), EmbeddedAsset::new(b"bnnuy")),
+            (Path::new("ui\\title.png"), title),
+            (Path::new("ui/./title.png"), other),
+            (Path::new("ui/title.png"), title),
+        ]));
+        assert_eq!(io.asset(Path::new("bnnuy.png")), Some(&EmbeddedAsset::new(b"bnnuy")));
+        assert_eq!(io.asset(Path::new("ui/title.png")), Some(&title));
+        let mut files = io.assets.keys().copied().collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, [Path::new("bnnuy.png"), Path::new("ui/title.png")]);
+        assert!(io
+            .read_directory(Path::new("ui"))
+            .unwrap()
+            .eq([PathBuf::from("ui/title.png")]));
+    }
 }
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, Metadata};
+use bevy::log::warn;
+
+use crate::{pack, paths};
+
+/// The size and hash of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AssetInfo {
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The 64-bit FNV-1a hash of the file.
+    pub hash: u64,
+}
+
+impl AssetInfo {
+    /// Measures and hashes `data`.
+    pub fn of(data: &[u8]) -> Self {
+        Self {
+            size: data.len() as u64,
+            hash: pack::fnv1a(data),
+        }
+    }
+}
+
+/// The size and hash of every embedded asset, as shipped.
+///
+/// Inserted as a resource by the [`EmbeddedAssetsPlugin`].
+///
+/// [`EmbeddedAssetsPlugin`]: crate::EmbeddedAssetsPlugin
+#[derive(Clone, Default, Debug)]
+pub struct AssetManifest {
+    assets: HashMap<PathBuf, AssetInfo>,
+}
+
+impl AssetManifest {
+    pub fn new(assets: impl IntoIterator<Item = (PathBuf, AssetInfo)>) -> Self {
+        Self {
+            assets: assets.into_iter().collect(),
+        }
+    }
+
+    /// Returns the shipped size and hash of the asset at `path`.
+    pub fn get(&self, path: &Path) -> Option<AssetInfo> {
+        self.assets.get(&paths::normalize(path)).copied()
+    }
+
+    /// Returns whether `data` is the shipped version of the asset at `path`,
+    /// or `None` if no such asset was shipped.
+    pub fn matches(&self, path: &Path, data: &[u8]) -> Option<bool> {
+        self.get(path).map(|info| info == AssetInfo::of(data))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, AssetInfo)> {
+        self.assets.iter().map(|(path, info)| (path.as_path(), *info))
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+}
+
+/// An [`AssetIo`] that logs a warning whenever an asset it loads differs
+/// from the shipped version of the asset it overrides.
+pub(crate) struct CheckedAssetIo {
+    inner: Box<dyn AssetIo>,
+    manifest: AssetManifest,
+}
+
+impl CheckedAssetIo {
+    pub fn new(inner: Box<dyn AssetIo>, manifest: AssetManifest) -> Self {
+        Self { inner, manifest }
+    }
+}
+
+impl AssetIo for CheckedAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            let data = self.inner.load_path(path).await?;
+            if let Some(shipped) = self.manifest.get(path) {
+                let loaded = AssetInfo::of(&data);
+                if loaded != shipped {
+                    warn!(
+                        "{} has been overridden: expected {} bytes with hash {:016x}, found {} bytes with hash {:016x}",
+                        path.display(),
+                        shipped.size,
+                        shipped.hash,
+                        loaded.size,
+                        loaded.hash,
+                    );
+                }
+            }
+            Ok(data)
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.inner.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        self.inner.get_metadata(path)
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        self.inner.watch_path_for_changes(path)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.inner.watch_for_changes()
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, Metadata};
+use bevy::prelude::{AssetServer, Res};
+
+use crate::alias::AssetAliases;
+use crate::index::AssetIndex;
+use crate::paths;
+
+/// Generated assets that are served through the [`AssetServer`] just like
+/// files, so that they can be loaded by path and use the same asset loaders.
+///
+/// The [`EmbeddedAssetsPlugin`] inserts one as a resource and serves its
+/// assets in front of every other source. Replacing an asset that has already
+/// been loaded reloads it, sending the same `AssetEvent::Modified` that hot
+/// reloading a file would.
+///
+/// ```ignore
+/// fn generate_level(memory: Res<MemoryAssets>, asset_server: Res<AssetServer>) {
+///     memory.insert("levels/generated.scn.ron", generate().into_bytes());
+///     let level: Handle<DynamicScene> = asset_server.load("levels/generated.scn.ron");
+/// }
+/// ```
+///
+/// [`EmbeddedAssetsPlugin`]: crate::EmbeddedAssetsPlugin
+#[derive(Clone, Default)]
+pub struct MemoryAssets {
+    state: Arc<RwLock<MemoryState>>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    index: AssetIndex,
+    assets: HashMap<PathBuf, Vec<u8>>,
+    /// Every path the [`AssetServer`] has loaded.
+    watched: HashSet<PathBuf>,
+    /// Watched paths that have been replaced since they were last reloaded.
+    changed: HashSet<PathBuf>,
+}
+
+impl MemoryAssets {
+    /// Stores `data` under `path`, replacing any asset that was already there.
+    pub fn insert(&self, path: impl AsRef<Path>, data: impl Into<Vec<u8>>) {
+        let path = paths::normalize(path.as_ref());
+        let mut state = self.state.write().unwrap();
+        state.index.insert(&path);
+        if state.watched.contains(&path) {
+            state.changed.insert(path.clone());
+        }
+        state.assets.insert(path, data.into());
+    }
+
+    /// Removes the asset at `path`, returning its contents.
+    pub fn remove(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        let path = paths::normalize(path.as_ref());
+        let mut state = self.state.write().unwrap();
+        state.index.remove(&path);
+        state.changed.remove(&path);
+        state.assets.remove(&path)
+    }
+
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        let path = paths::normalize(path.as_ref());
+        self.state.read().unwrap().assets.contains_key(&path)
+    }
+
+    /// Returns every loaded asset that was replaced since the last call.
+    pub(crate) fn changed(&self) -> Vec<PathBuf> {
+        self.state.write().unwrap().changed.drain().collect()
+    }
+}
+
+impl AssetIo for MemoryAssets {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            let state = self.state.read().unwrap();
+            match state.assets.get(&paths::normalize(path)) {
+                Some(data) => Ok(data.clone()),
+                None => Err(AssetIoError::NotFound(path.to_path_buf())),
+            }
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.state.read().unwrap().index.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        self.state.read().unwrap().index.get_metadata(path)
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        let path = paths::normalize(path);
+        self.state.write().unwrap().watched.insert(path);
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
+
+impl Debug for MemoryAssets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state.read().unwrap();
+        f.debug_struct("MemoryAssets")
+            .field("assets", &state.assets.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Reloads every loaded asset that has been replaced in [`MemoryAssets`].
+pub(crate) fn reload_changed_memory_assets(
+    asset_server: Res<AssetServer>,
+    memory: Res<MemoryAssets>,
+    aliases: Res<AssetAliases>,
+) {
+    for path in aliases.requesters(memory.changed()) {
+        asset_server.reload_asset(path.as_path());
+    }
+}
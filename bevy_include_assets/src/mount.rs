@@ -0,0 +1,201 @@
+use std::collections::BTreeSet;
+use std::fmt::{self, Debug, Formatter};
+#[cfg(all(feature = "signing", any(feature = "zip", feature = "tar")))]
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, FileType, Metadata};
+
+use crate::{layered, paths};
+#[cfg(all(feature = "signing", any(feature = "zip", feature = "tar")))]
+use crate::{ArchiveAssetIo, PackVerifier};
+
+/// A registry of [`AssetIo`]s mounted under path prefixes, which can be
+/// changed while the game is running.
+///
+/// The [`EmbeddedAssetsPlugin`] inserts one as a resource and serves mounted
+/// assets in front of embedded ones, so seasonal content can be enabled with
+///
+/// ```ignore
+/// fn enable_spring(mounts: Res<AssetMounts>) {
+///     mounts.mount("dlc/spring", ArchiveAssetIo::open("spring.pak").unwrap());
+/// }
+/// ```
+///
+/// after which `dlc/spring/flower.png` is read from `flower.png` in `spring.pak`.
+/// Assets that were already loaded aren't reloaded when their mount changes.
+///
+/// [`EmbeddedAssetsPlugin`]: crate::EmbeddedAssetsPlugin
+#[derive(Clone, Default)]
+pub struct AssetMounts {
+    /// Later mounts take precedence over earlier ones.
+    mounts: Arc<RwLock<Vec<Mount>>>,
+}
+
+struct Mount {
+    prefix: PathBuf,
+    io: Arc<dyn AssetIo>,
+}
+
+impl AssetMounts {
+    /// Serves the assets in `io` under `prefix`, replacing anything that was
+    /// previously mounted there.
+    ///
+    /// Nothing mounted this way is checked against a signature, even if the
+    /// [`EmbeddedAssetsPlugin`] has a verifier.
+    pub fn mount(&self, prefix: impl AsRef<Path>, io: impl AssetIo) {
+        self.mount_boxed(prefix.as_ref(), Box::new(io));
+    }
+
+    /// Serves the assets in the archive at `archive` under `prefix`, as long as
+    /// `verifier` accepts its signature (see [`ArchiveAssetIo::open_verified`]).
+    #[cfg(all(feature = "signing", any(feature = "zip", feature = "tar")))]
+    pub fn mount_verified(
+        &self,
+        prefix: impl AsRef<Path>,
+        archive: impl AsRef<Path>,
+        verifier: &PackVerifier,
+    ) -> io::Result<()> {
+        self.mount(prefix, ArchiveAssetIo::open_verified(archive, verifier)?);
+        Ok(())
+    }
+
+    pub(crate) fn mount_boxed(&self, prefix: &Path, io: Box<dyn AssetIo>) {
+        let prefix = paths::normalize(prefix);
+        let mut mounts = self.mounts.write().unwrap();
+        mounts.retain(|mount| mount.prefix != prefix);
+        mounts.push(Mount {
+            prefix,
+            io: Arc::from(io),
+        });
+    }
+
+    /// Removes whatever is mounted under `prefix`, returning whether anything was.
+    pub fn unmount(&self, prefix: impl AsRef<Path>) -> bool {
+        let prefix = paths::normalize(prefix.as_ref());
+        let mut mounts = self.mounts.write().unwrap();
+        let len = mounts.len();
+        mounts.retain(|mount| mount.prefix != prefix);
+        mounts.len() != len
+    }
+
+    pub fn is_mounted(&self, prefix: impl AsRef<Path>) -> bool {
+        let prefix = paths::normalize(prefix.as_ref());
+        self.mounts.read().unwrap().iter().any(|mount| mount.prefix == prefix)
+    }
+
+    /// Returns every prefix something is mounted under, from lowest to highest precedence.
+    pub fn prefixes(&self) -> Vec<PathBuf> {
+        self.mounts
+            .read()
+            .unwrap()
+            .iter()
+            .map(|mount| mount.prefix.clone())
+            .collect()
+    }
+
+    /// Returns every mount that `path` falls under, from highest to lowest
+    /// precedence, along with the path relative to that mount.
+    fn resolve(&self, path: &Path) -> Vec<(PathBuf, Arc<dyn AssetIo>, PathBuf)> {
+        let mounts = self.mounts.read().unwrap();
+        mounts
+            .iter()
+            .rev()
+            .filter_map(|mount| {
+                let relative = path.strip_prefix(&mount.prefix).ok()?;
+                Some((mount.prefix.clone(), mount.io.clone(), relative.to_path_buf()))
+            })
+            .collect()
+    }
+
+    /// Returns the children of `dir` that only exist because something is mounted below them.
+    fn mount_points_in(&self, dir: &Path) -> BTreeSet<PathBuf> {
+        let mounts = self.mounts.read().unwrap();
+        mounts
+            .iter()
+            .filter_map(
+                |mount| match mount.prefix.strip_prefix(dir).ok()?.components().next()? {
+                    Component::Normal(child) => Some(dir.join(child)),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+}
+
+impl AssetIo for AssetMounts {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            // the lock can't be held across awaits
+            for (_, io, relative) in self.resolve(&paths::normalize(path)) {
+                match io.load_path(&relative).await {
+                    Err(err) if layered::is_not_found(&err) => continue,
+                    result => return result,
+                }
+            }
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let dir = paths::normalize(path);
+        let mut entries = self.mount_points_in(&dir);
+        let mut found = !entries.is_empty();
+        for (prefix, io, relative) in self.resolve(&dir) {
+            match io.read_directory(&relative) {
+                Ok(children) => {
+                    found = true;
+                    entries.extend(children.map(|child| prefix.join(child)));
+                }
+                Err(err) if layered::is_not_found(&err) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        if found {
+            Ok(Box::new(entries.into_iter()))
+        } else {
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        }
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        let key = paths::normalize(path);
+        for (_, io, relative) in self.resolve(&key) {
+            match io.get_metadata(&relative) {
+                Err(err) if layered::is_not_found(&err) => continue,
+                result => return result,
+            }
+        }
+        let mounts = self.mounts.read().unwrap();
+        if mounts.iter().any(|mount| mount.prefix.starts_with(&key)) {
+            Ok(Metadata::new(FileType::Directory))
+        } else {
+            Err(AssetIoError::NotFound(path.to_path_buf()))
+        }
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        // the path usually only exists in some of the mounts, which is fine
+        for (_, io, relative) in self.resolve(&paths::normalize(path)) {
+            let _ = io.watch_path_for_changes(&relative);
+        }
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        let mounts = self.mounts.read().unwrap();
+        for mount in mounts.iter() {
+            mount.io.watch_for_changes()?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for AssetMounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetMounts")
+            .field("prefixes", &self.prefixes())
+            .finish()
+    }
+}
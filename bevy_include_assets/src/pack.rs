@@ -0,0 +1,98 @@
+//! Reads the asset packs written by `include_assets!`.
+//! See `bevy_include_assets_macros/src/pack.rs` for the format.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{EmbeddedAsset, EmbeddedAssets};
+
+const MAGIC: &[u8; 8] = b"BIAPACK1";
+
+/// Set in an entry's flags if its data is deflated.
+const DEFLATED: u8 = 1;
+
+/// Set in an entry's flags if its data is encrypted.
+const ENCRYPTED: u8 = 2;
+
+/// Parses a pack, returning `None` if it is malformed.
+pub(crate) fn parse(pack: &'static [u8]) -> Option<EmbeddedAssets> {
+    let mut reader = Reader(pack.strip_prefix(MAGIC)?);
+    let count = u32::from_le_bytes(reader.array()?);
+
+    let mut entries = Vec::with_capacity(count.min(1024) as usize);
+    for _ in 0..count {
+        let path_len = u16::from_le_bytes(reader.array()?);
+        let path = std::str::from_utf8(reader.take(path_len as usize)?).ok()?;
+        let [flags] = reader.array()?;
+        let offset = u64::from_le_bytes(reader.array()?);
+        let stored_len = u64::from_le_bytes(reader.array()?);
+        let size = u64::from_le_bytes(reader.array()?);
+        let hash = u64::from_le_bytes(reader.array()?);
+        entries.push((path, flags, offset, stored_len, size, hash));
+    }
+
+    let data = reader.0;
+    let mut assets = HashMap::with_capacity(entries.len());
+    for (path, flags, offset, stored_len, size, hash) in entries {
+        let start = usize::try_from(offset).ok()?;
+        let end = start.checked_add(usize::try_from(stored_len).ok()?)?;
+        let asset = EmbeddedAsset {
+            data: data.get(start..end)?,
+            deflated: flags & DEFLATED != 0,
+            encrypted: flags & ENCRYPTED != 0,
+            size,
+            hash,
+        };
+        assets.insert(Path::new(path), asset);
+    }
+
+    Some(EmbeddedAssets {
+        assets,
+        ..EmbeddedAssets::default()
+    })
+}
+
+/// Finds every non-empty pack in a binary, such as a game executable.
+pub(crate) fn find(binary: &'static [u8]) -> Vec<EmbeddedAssets> {
+    let mut packs = Vec::new();
+    let mut rest = binary;
+    while let Some(start) = rest.windows(MAGIC.len()).position(|window| window == MAGIC) {
+        rest = &rest[start..];
+        match parse(rest) {
+            Some(pack) if !pack.assets.is_empty() => packs.push(pack),
+            // the magic can also show up on its own, like in this very function
+            _ => {}
+        }
+        rest = &rest[MAGIC.len()..];
+    }
+    packs
+}
+
+/// The 64-bit FNV-1a hash of `data`.
+pub(crate) const fn fnv1a(data: &[u8]) -> u64 {
+    // iterators can't be used in constants
+    let mut hash = 0xcbf29ce484222325u64;
+    let mut i = 0;
+    while i < data.len() {
+        hash = (hash ^ data[i] as u64).wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+struct Reader(&'static [u8]);
+
+impl Reader {
+    fn take(&mut self, len: usize) -> Option<&'static [u8]> {
+        if len > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N).map(|bytes| bytes.try_into().unwrap())
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+/// Resolves `.` and `..` components and treats `\` as a separator, so that
+/// every spelling of an embedded asset's path maps to the same key.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let path = path.to_string_lossy();
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components.into_iter().collect()
+}
+
+/// Case-folds an already normalized path for case-insensitive lookups.
+pub(crate) fn fold(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_resolves_every_spelling() {
+        for path in [
+            "ui/title.png",
+            "./ui/title.png",
+            "/ui//title.png",
+            "ui\\title.png",
+            "fonts/../ui/./title.png",
+        ] {
+            assert_eq!(normalize(Path::new(path)), Path::new("ui/title.png"), "{}", path);
+        }
+        assert_eq!(normalize(Path::new("../..")), Path::new(""));
+        assert_eq!(normalize(Path::new(".")), Path::new(""));
+    }
+
+    #[test]
+    fn fold_ignores_case() {
+        assert_eq!(fold(Path::new("UI/Title.PNG")), fold(Path::new("ui/title.png")));
+    }
+}
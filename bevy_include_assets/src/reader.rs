@@ -0,0 +1,100 @@
+use std::borrow::Cow;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+/// A reader over an embedded asset's original contents.
+///
+/// Stored and encrypted assets are read straight out of the binary, a few
+/// bytes at a time, while deflated ones have to be inflated up front.
+/// Returned by [`EmbeddedAsset::reader`](crate::EmbeddedAsset::reader).
+#[derive(Clone, Debug)]
+pub struct EmbeddedAssetReader {
+    data: Cow<'static, [u8]>,
+    position: u64,
+    /// The nonce `data` is still encrypted with, if it is.
+    #[cfg(feature = "encrypt")]
+    nonce: Option<u64>,
+}
+
+impl EmbeddedAssetReader {
+    pub(crate) fn new(data: Cow<'static, [u8]>) -> Self {
+        Self {
+            data,
+            position: 0,
+            #[cfg(feature = "encrypt")]
+            nonce: None,
+        }
+    }
+
+    #[cfg(feature = "encrypt")]
+    pub(crate) fn encrypted(data: &'static [u8], nonce: u64) -> Self {
+        Self {
+            nonce: Some(nonce),
+            ..Self::new(Cow::Borrowed(data))
+        }
+    }
+
+    /// The size of the asset in bytes.
+    pub fn len(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the bytes in `range`, borrowing them from the binary if they
+    /// don't need decoding. This doesn't move the reader.
+    pub fn read_range(&self, range: Range<u64>) -> io::Result<Cow<'static, [u8]>> {
+        if range.start > range.end || range.end > self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{}..{} is out of bounds for an asset of {} bytes",
+                    range.start,
+                    range.end,
+                    self.len()
+                ),
+            ));
+        }
+        let (start, end) = (range.start as usize, range.end as usize);
+        let bytes = match &self.data {
+            Cow::Borrowed(data) => Cow::Borrowed(&data[start..end]),
+            Cow::Owned(data) => Cow::Owned(data[start..end].to_vec()),
+        };
+        #[cfg(feature = "encrypt")]
+        if let Some(nonce) = self.nonce {
+            let mut bytes = bytes.into_owned();
+            crate::encrypt::decrypt_at(&mut bytes, nonce, range.start)?;
+            return Ok(Cow::Owned(bytes));
+        }
+        Ok(bytes)
+    }
+}
+
+impl Read for EmbeddedAssetReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = self.position.min(self.len()) as usize;
+        let len = buf.len().min(self.data.len() - start);
+        buf[..len].copy_from_slice(&self.data[start..start + len]);
+        #[cfg(feature = "encrypt")]
+        if let Some(nonce) = self.nonce {
+            crate::encrypt::decrypt_at(&mut buf[..len], nonce, start as u64)?;
+        }
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl Seek for EmbeddedAssetReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "can't seek before the start of an asset"))?;
+        Ok(self.position)
+    }
+}
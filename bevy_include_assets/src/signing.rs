@@ -0,0 +1,130 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[cfg(any(feature = "zip", feature = "tar"))]
+use bevy::log::warn;
+use ed25519_dalek::{Digest, Keypair, PublicKey, SecretKey, Sha512, Signature};
+
+/// Distinguishes pack signatures from ed25519 signatures made for anything else.
+const CONTEXT: &[u8] = b"bevy_include_assets pack";
+
+/// What to do with a pack whose signature is missing or doesn't match.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum SignaturePolicy {
+    /// Log an error and don't load the pack.
+    #[default]
+    Reject,
+    /// Log a warning and load the pack anyway.
+    Warn,
+}
+
+/// Checks that asset packs were signed with the secret key matching a public
+/// key compiled into the game, so that only official packs can override
+/// embedded assets.
+///
+/// ```ignore
+/// let verifier = PackVerifier::new(*include_bytes!("../pack.pub"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PackVerifier {
+    key: PublicKey,
+    policy: SignaturePolicy,
+}
+
+impl PackVerifier {
+    /// # Panics
+    ///
+    /// Panics if `public_key` isn't a valid ed25519 public key.
+    pub fn new(public_key: [u8; 32]) -> Self {
+        Self {
+            key: PublicKey::from_bytes(&public_key).expect("invalid ed25519 public key"),
+            policy: SignaturePolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: SignaturePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Checks that `pack` was signed by `signature`.
+    pub fn verify(&self, pack: impl Read, signature: Option<&[u8; 64]>) -> io::Result<()> {
+        let signature = signature.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "pack isn't signed"))?;
+        self.key
+            .verify_prehashed(digest(pack)?, Some(CONTEXT), &Signature::from(*signature))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "pack signature doesn't match"))
+    }
+
+    /// Checks a standalone pack against the signature next to it (see [`signature_path`]).
+    pub fn verify_file(&self, pack: &Path) -> io::Result<()> {
+        self.verify(File::open(pack)?, read_signature(pack)?.as_ref())
+    }
+
+    /// Verifies `data`, the contents of the pack at `pack`, returning an error
+    /// if the policy says it shouldn't be loaded.
+    #[cfg(any(feature = "zip", feature = "tar"))]
+    pub(crate) fn check(&self, pack: &Path, data: &[u8], signature: Option<&[u8; 64]>) -> io::Result<()> {
+        match (self.verify(data, signature), self.policy) {
+            (Err(err), SignaturePolicy::Warn) => {
+                warn!("Loading asset pack {} anyway: {}", pack.display(), err);
+                Ok(())
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+/// Signs `pack` with a 32-byte ed25519 secret key.
+pub fn sign_pack(pack: impl Read, secret_key: &[u8; 32]) -> io::Result<[u8; 64]> {
+    let keypair = keypair(secret_key)?;
+    let signature = keypair
+        .sign_prehashed(digest(pack)?, Some(CONTEXT))
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    Ok(signature.to_bytes())
+}
+
+/// Returns the public key that verifies packs signed with `secret_key`.
+pub fn public_key(secret_key: &[u8; 32]) -> io::Result<[u8; 32]> {
+    Ok(keypair(secret_key)?.public.to_bytes())
+}
+
+/// Returns where the signature of a standalone pack is kept, which is the
+/// pack's path with `.sig` appended.
+pub fn signature_path(pack: &Path) -> PathBuf {
+    let mut path = OsString::from(pack);
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Reads the signature kept next to a standalone pack, if there is one.
+pub(crate) fn read_signature(pack: &Path) -> io::Result<Option<[u8; 64]>> {
+    match fs::read(signature_path(pack)) {
+        Ok(signature) => signature
+            .try_into()
+            .map(Some)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "pack signature is malformed")),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn keypair(secret_key: &[u8; 32]) -> io::Result<Keypair> {
+    let secret = SecretKey::from_bytes(secret_key).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let public = PublicKey::from(&secret);
+    Ok(Keypair { secret, public })
+}
+
+fn digest(mut pack: impl Read) -> io::Result<Sha512> {
+    let mut digest = Sha512::new();
+    let mut buf = [0; 8192];
+    loop {
+        match pack.read(&mut buf) {
+            Ok(0) => return Ok(digest),
+            Ok(read) => digest.update(&buf[..read]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
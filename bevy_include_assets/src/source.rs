@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use std::{fs, io};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, FileType, Metadata};
+use bevy::prelude::{AssetServer, Res};
+
+use crate::alias::AssetAliases;
+use crate::paths;
+
+/// How often watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An [`AssetIo`] that reads assets straight from a directory on disk, like
+/// the one they were embedded from, so that they can be edited while the game
+/// is running.
+///
+/// Bevy's own change detection only works with a bare `FileAssetIo`, so
+/// watched files are instead polled by [`reload_changed_sources`].
+#[derive(Clone, Debug)]
+pub(crate) struct SourceAssetIo {
+    root: PathBuf,
+    /// Where the embedded assets are mounted.
+    prefix: PathBuf,
+    /// Where the asset server sees this source, if it is behind an [`AssetMounts`](crate::AssetMounts).
+    mount_point: PathBuf,
+    /// The only assets served, if set, each with its file relative to the root.
+    files: Option<Arc<HashMap<PathBuf, PathBuf>>>,
+    watched: Arc<Mutex<Watched>>,
+}
+
+#[derive(Debug, Default)]
+struct Watched {
+    /// Each watched path and when its file was last modified, if it still exists.
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    polled: Option<Instant>,
+}
+
+impl SourceAssetIo {
+    pub fn new(root: impl Into<PathBuf>, prefix: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            prefix: prefix.into(),
+            mount_point: PathBuf::new(),
+            files: None,
+            watched: Arc::default(),
+        }
+    }
+
+    /// Only serves the assets in `files`, each from the file it maps to,
+    /// instead of everything under the root.
+    pub fn with_files(mut self, files: HashMap<PathBuf, PathBuf>) -> Self {
+        self.files = Some(Arc::new(files));
+        self
+    }
+
+    /// Reports changed files under `mount_point`, for sources the asset server sees mounted there.
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+    pub fn mounted_at(mut self, mount_point: impl Into<PathBuf>) -> Self {
+        self.mount_point = mount_point.into();
+        self
+    }
+
+    /// Returns where the asset at `path` is on disk, if it falls under the prefix.
+    fn file(&self, path: &Path) -> Result<PathBuf, AssetIoError> {
+        let key = paths::normalize(path);
+        let relative = key.strip_prefix(&self.prefix).ok();
+        let relative = match &self.files {
+            Some(files) => relative.and_then(|relative| files.get(relative)).map(PathBuf::as_path),
+            None => relative,
+        };
+        match relative {
+            Some(relative) => Ok(self.root.join(relative)),
+            None => Err(AssetIoError::NotFound(path.to_path_buf())),
+        }
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        fs::metadata(self.file(path).ok()?)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Returns every watched path that was modified since the last call,
+    /// checking at most once every [`POLL_INTERVAL`].
+    pub fn changed(&self) -> Vec<PathBuf> {
+        let paths = {
+            let mut watched = self.watched.lock().unwrap();
+            if watched.polled.map_or(false, |polled| polled.elapsed() < POLL_INTERVAL) {
+                return Vec::new();
+            }
+            watched.polled = Some(Instant::now());
+            watched.files.keys().cloned().collect::<Vec<_>>()
+        };
+        // files are checked without holding the lock, so that loads aren't held up
+        let modified = paths
+            .into_iter()
+            .map(|path| {
+                let modified = self.modified(&path);
+                (path, modified)
+            })
+            .collect::<Vec<_>>();
+
+        let mut watched = self.watched.lock().unwrap();
+        let mut changed = Vec::new();
+        for (path, modified) in modified {
+            if let Some(last_modified) = watched.files.get_mut(&path) {
+                if modified != *last_modified {
+                    *last_modified = modified;
+                    changed.push(self.mount_point.join(path));
+                }
+            }
+        }
+        changed
+    }
+}
+
+impl AssetIo for SourceAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            fs::read(self.file(path)?).map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => AssetIoError::NotFound(path.to_path_buf()),
+                _ => err.into(),
+            })
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let path = paths::normalize(path);
+        let entries = fs::read_dir(self.file(&path)?).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => AssetIoError::NotFound(path.clone()),
+            _ => err.into(),
+        })?;
+        let entries = entries
+            .map(|entry| entry.map(|entry| path.join(entry.file_name())))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Box::new(entries.into_iter()))
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        match fs::metadata(self.file(path)?) {
+            Ok(metadata) if metadata.is_dir() => Ok(Metadata::new(FileType::Directory)),
+            Ok(_) => Ok(Metadata::new(FileType::File)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Err(AssetIoError::NotFound(path.to_path_buf())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        // every path the asset server loads ends up here, but only files under the root can change
+        if let Some(modified) = self.modified(path) {
+            let mut watched = self.watched.lock().unwrap();
+            watched.files.insert(path.to_path_buf(), Some(modified));
+        }
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
+
+/// Every [`SourceAssetIo`] the asset server reads from.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct PolledSources(pub Vec<SourceAssetIo>);
+
+/// Reloads every asset whose source file has changed on disk.
+pub(crate) fn reload_changed_sources(
+    asset_server: Res<AssetServer>,
+    sources: Res<PolledSources>,
+    aliases: Res<AssetAliases>,
+) {
+    // the same path can be watched by several sources, which the set takes care of
+    for path in aliases.requesters(sources.0.iter().flat_map(SourceAssetIo::changed)) {
+        asset_server.reload_asset(path.as_path());
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use bevy::app::{App, StartupStage};
+use bevy::prelude::{AssetServer, Commands, Plugin, Res};
+
+use crate::EmbeddedAssets;
+
+/// A struct of typed asset handles generated by [`include_asset_table!`].
+///
+/// [`include_asset_table!`]: crate::include_asset_table
+pub trait AssetTable: Send + Sync + Sized + 'static {
+    /// The path of every asset in the table.
+    const PATHS: &'static [&'static str];
+
+    /// Returns the table's assets, ready to be passed to [`EmbeddedAssetsPlugin::new`].
+    ///
+    /// [`EmbeddedAssetsPlugin::new`]: crate::EmbeddedAssetsPlugin::new
+    fn embedded() -> EmbeddedAssets;
+
+    /// Starts loading every asset in the table.
+    fn load(asset_server: &AssetServer) -> Self;
+}
+
+/// Loads an [`AssetTable`] and inserts it as a resource before any startup
+/// systems run, so that they can take it as a `Res<T>`.
+pub struct AssetTablePlugin<T> {
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for AssetTablePlugin<T> {
+    fn default() -> Self {
+        Self { marker: PhantomData }
+    }
+}
+
+impl<T> Debug for AssetTablePlugin<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetTablePlugin").finish()
+    }
+}
+
+impl<T: AssetTable> Plugin for AssetTablePlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_asset_table::<T>);
+    }
+
+    fn name(&self) -> &str {
+        "AssetTablePlugin"
+    }
+}
+
+/// Loads an [`AssetTable`] and inserts it as a resource.
+pub fn load_asset_table<T: AssetTable>(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(T::load(&asset_server));
+}
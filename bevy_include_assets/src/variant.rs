@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+use std::fmt::{self, Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use bevy::asset::{AssetIo, AssetIoError, BoxedFuture, Metadata};
+use bevy::prelude::{AssetServer, Res};
+
+use crate::alias::AssetAliases;
+use crate::paths;
+
+/// The active locale, pixel density and platform, which pick between
+/// variants of an asset.
+///
+/// The [`EmbeddedAssetsPlugin`] inserts one as a resource. Loading `ui/title.png`
+/// with the locale set to `fr-CA`, a scale of 2 and the platform set to `android`
+/// loads the first of these that exists:
+///
+/// ```text
+/// ui/title@2x.android.fr-CA.png
+/// ui/title@2x.fr-CA.png
+/// ui/title.android.fr-CA.png
+/// ui/title.fr-CA.png
+/// ui/title@2x.android.fr.png
+/// ui/title@2x.fr.png
+/// ui/title.android.fr.png
+/// ui/title.fr.png
+/// ui/title@2x.android.png
+/// ui/title@2x.png
+/// ui/title.android.png
+/// ui/title.png
+/// ```
+///
+/// That is, a matching locale matters most, then pixel density (from the
+/// scale down to `@2x`), then platform. Changing a setting reloads every
+/// loaded asset that has variants.
+///
+/// ```ignore
+/// fn switch_to_french(variants: Res<AssetVariants>) {
+///     variants.set_locale(Some("fr"));
+/// }
+/// ```
+///
+/// [`EmbeddedAssetsPlugin`]: crate::EmbeddedAssetsPlugin
+#[derive(Clone, Default)]
+pub struct AssetVariants {
+    state: Arc<RwLock<VariantState>>,
+}
+
+#[derive(Default)]
+struct VariantState {
+    locale: Option<String>,
+    scale: u32,
+    platform: Option<String>,
+    /// Every loaded path that has at least one variant.
+    varied: HashSet<PathBuf>,
+    /// Whether a setting has changed since loaded assets were last reloaded.
+    changed: bool,
+}
+
+impl AssetVariants {
+    pub fn locale(&self) -> Option<String> {
+        self.state.read().unwrap().locale.clone()
+    }
+
+    /// Sets the locale as a BCP 47 tag like `fr` or `pt-BR`. Less specific
+    /// locales are also tried, so `pt-BR` falls back to `pt`.
+    pub fn set_locale(&self, locale: Option<&str>) {
+        let mut state = self.state.write().unwrap();
+        if state.locale.as_deref() != locale {
+            state.locale = locale.map(str::to_owned);
+            state.changed = true;
+        }
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.state.read().unwrap().scale.max(1)
+    }
+
+    /// Sets the pixel density, so that a scale of 3 prefers `@3x` variants
+    /// and then `@2x` ones.
+    pub fn set_scale(&self, scale: u32) {
+        let mut state = self.state.write().unwrap();
+        if state.scale.max(1) != scale.max(1) {
+            state.scale = scale;
+            state.changed = true;
+        }
+    }
+
+    pub fn platform(&self) -> Option<String> {
+        self.state.read().unwrap().platform.clone()
+    }
+
+    /// Sets the platform, e.g. to [`std::env::consts::OS`].
+    pub fn set_platform(&self, platform: Option<&str>) {
+        let mut state = self.state.write().unwrap();
+        if state.platform.as_deref() != platform {
+            state.platform = platform.map(str::to_owned);
+            state.changed = true;
+        }
+    }
+
+    /// Returns every path `path` could resolve to in order, ending with `path` itself.
+    fn candidates(&self, path: &Path) -> Vec<PathBuf> {
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(file_name) => file_name,
+            None => return vec![path.to_path_buf()],
+        };
+        let (stem, extension) = split_extension(file_name);
+
+        let state = self.state.read().unwrap();
+        let mut locales = Vec::new();
+        if let Some(locale) = &state.locale {
+            let mut locale = locale.as_str();
+            locales.push(Some(locale));
+            while let Some((parent, _)) = locale.rsplit_once(['-', '_']) {
+                locales.push(Some(parent));
+                locale = parent;
+            }
+        }
+        locales.push(None);
+        let scales = (2..=state.scale).rev().map(Some).chain([None]).collect::<Vec<_>>();
+        let platforms = state
+            .platform
+            .as_deref()
+            .map(Some)
+            .into_iter()
+            .chain([None])
+            .collect::<Vec<_>>();
+
+        let mut candidates = Vec::new();
+        for locale in &locales {
+            for scale in &scales {
+                for platform in &platforms {
+                    let mut name = stem.to_owned();
+                    if let Some(scale) = scale {
+                        name += &format!("@{}x", scale);
+                    }
+                    for suffix in [platform, locale].into_iter().flatten() {
+                        name += &format!(".{}", suffix);
+                    }
+                    name += extension;
+                    candidates.push(path.with_file_name(name));
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Returns whether any setting changed since the last call.
+    fn changed(&self) -> bool {
+        std::mem::take(&mut self.state.write().unwrap().changed)
+    }
+}
+
+impl Debug for AssetVariants {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state.read().unwrap();
+        f.debug_struct("AssetVariants")
+            .field("locale", &state.locale)
+            .field("scale", &state.scale.max(1))
+            .field("platform", &state.platform)
+            .finish()
+    }
+}
+
+/// Splits a file name into its stem and its extension, including the dot.
+///
+/// The extension starts at the first dot, so that e.g. `level.scn.ron`
+/// keeps its full extension.
+fn split_extension(file_name: &str) -> (&str, &str) {
+    match file_name.find('.') {
+        Some(0) | None => (file_name, ""),
+        Some(dot) => file_name.split_at(dot),
+    }
+}
+
+/// An [`AssetIo`] that loads the best variant of each asset according to
+/// [`AssetVariants`].
+pub(crate) struct VariantAssetIo {
+    inner: Box<dyn AssetIo>,
+    variants: AssetVariants,
+    loaded: AssetAliases,
+}
+
+impl VariantAssetIo {
+    pub fn new(inner: Box<dyn AssetIo>, variants: AssetVariants, loaded: AssetAliases) -> Self {
+        Self {
+            inner,
+            variants,
+            loaded,
+        }
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        let mut candidates = self.variants.candidates(&paths::normalize(path));
+        candidates.pop();
+        candidates
+            .into_iter()
+            .find(|candidate| matches!(self.inner.get_metadata(candidate), Ok(metadata) if metadata.is_file()))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Returns whether a variant of `path` exists, whatever the current settings are.
+    fn has_variants(&self, path: &Path) -> bool {
+        let path = paths::normalize(path);
+        let (file_name, parent) = match (path.file_name().and_then(|name| name.to_str()), path.parent()) {
+            (Some(file_name), Some(parent)) => (file_name, parent),
+            _ => return false,
+        };
+        let (stem, extension) = split_extension(file_name);
+        let entries = match self.inner.read_directory(parent) {
+            Ok(entries) => entries,
+            Err(_) => return false,
+        };
+        entries
+            .filter_map(|entry| entry.file_name()?.to_str().map(str::to_owned))
+            .any(|name| {
+                name.strip_prefix(stem)
+                    .and_then(|name| name.strip_suffix(extension))
+                    .map_or(false, |variant| variant.starts_with(['@', '.']))
+            })
+    }
+}
+
+impl AssetIo for VariantAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            let resolved = self.resolve(path);
+            match self.inner.load_path(&resolved).await {
+                // the path the asset was requested under is the one worth reporting
+                Err(AssetIoError::NotFound(_)) => Err(AssetIoError::NotFound(path.to_path_buf())),
+                result => result,
+            }
+        })
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.inner.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        self.inner.get_metadata(path)
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        // called for every path the asset server loads
+        if self.has_variants(path) {
+            self.variants.state.write().unwrap().varied.insert(path.to_path_buf());
+        }
+        // the variant that was served is the file that can change
+        let resolved = self.resolve(path);
+        self.loaded.link(path, &resolved);
+        self.inner.watch_path_for_changes(&resolved)
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.inner.watch_for_changes()
+    }
+}
+
+/// Reloads every loaded asset with variants when [`AssetVariants`] changes.
+pub(crate) fn reload_changed_variants(
+    asset_server: Res<AssetServer>,
+    variants: Res<AssetVariants>,
+    aliases: Res<AssetAliases>,
+) {
+    if variants.changed() {
+        let varied = variants.state.read().unwrap().varied.clone();
+        for path in aliases.requesters(varied) {
+            asset_server.reload_asset(path.as_path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::alias::AliasAssetIo;
+    use crate::MemoryAssets;
+
+    #[test]
+    fn candidates_go_from_most_to_least_specific() {
+        let variants = AssetVariants::default();
+        variants.set_locale(Some("pt-BR"));
+        variants.set_scale(2);
+        let candidates = variants.candidates(Path::new("ui/title.png"));
+        assert_eq!(
+            candidates,
+            [
+                "ui/title@2x.pt-BR.png",
+                "ui/title.pt-BR.png",
+                "ui/title@2x.pt.png",
+                "ui/title.pt.png",
+                "ui/title@2x.png",
+                "ui/title.png",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn candidates_put_the_platform_before_the_locale() {
+        let variants = AssetVariants::default();
+        variants.set_locale(Some("fr"));
+        variants.set_platform(Some("android"));
+        let candidates = variants.candidates(Path::new("level.scn.ron"));
+        assert_eq!(
+            candidates,
+            [
+                "level.android.fr.scn.ron",
+                "level.fr.scn.ron",
+                "level.android.scn.ron",
+                "level.scn.ron"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn candidates_without_settings_are_just_the_path() {
+        let candidates = AssetVariants::default().candidates(Path::new(".hidden"));
+        assert_eq!(candidates, [PathBuf::from(".hidden")]);
+    }
+
+    #[test]
+    fn changing_a_variant_reloads_the_logical_path() {
+        let memory = MemoryAssets::default();
+        memory.insert("ui/title.png", "title");
+        memory.insert("ui/title.fr.png", "titre");
+        let variants = AssetVariants::default();
+        variants.set_locale(Some("fr"));
+        let loaded = AssetAliases::default();
+        let io = AliasAssetIo::new(
+            Box::new(VariantAssetIo::new(Box::new(memory.clone()), variants, loaded.clone())),
+            &[],
+            loaded.clone(),
+        );
+        io.watch_path_for_changes(Path::new("ui/title.png")).unwrap();
+
+        memory.insert("ui/title.fr.png", "nouveau titre");
+        let reloaded = loaded.requesters(memory.changed());
+
+        assert_eq!(reloaded, BTreeSet::from([PathBuf::from("ui/title.png")]));
+    }
+}
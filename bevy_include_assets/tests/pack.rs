@@ -0,0 +1,97 @@
+//! Round trips assets through the pack format written by `include_asset_dir!`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy_include_assets::{include_asset_dir, EmbeddedAssets};
+
+fn assert_round_trips(assets: EmbeddedAssets) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/assets");
+    let mut paths = assets.assets.keys().map(|path| path.to_path_buf()).collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(
+        paths,
+        ["bnnuy.txt", "empty.txt", "nested/bnnuy.txt", "noise.bin"].map(PathBuf::from)
+    );
+
+    for (path, asset) in &assets.assets {
+        let expected = fs::read(dir.join(path)).unwrap();
+        assert_eq!(asset.contents().unwrap(), expected, "{}", path.display());
+        assert_eq!(asset.size, expected.len() as u64);
+        asset.verify().unwrap();
+    }
+
+    // identical files share their data
+    let bnnuy = assets.assets[Path::new("bnnuy.txt")];
+    let nested = assets.assets[Path::new("nested/bnnuy.txt")];
+    assert_eq!(bnnuy.data.as_ptr(), nested.data.as_ptr());
+}
+
+#[test]
+fn stored() {
+    let assets = include_asset_dir!("assets"; compress = false);
+    assert!(assets.assets.values().all(|asset| !asset.deflated));
+    assert_eq!(assets.source_dir.is_some(), cfg!(debug_assertions));
+    assert_round_trips(assets);
+}
+
+#[cfg(feature = "compress")]
+#[test]
+fn deflated() {
+    let assets = include_asset_dir!("assets");
+    assert!(assets.assets[Path::new("bnnuy.txt")].deflated);
+    // deflating doesn't pay off for random data
+    assert!(!assets.assets[Path::new("noise.bin")].deflated);
+    assert_round_trips(assets);
+}
+
+/// Needs `BEVY_INCLUDE_ASSETS_KEY` to be set at build time, like any build with the `encrypt` feature.
+#[cfg(feature = "encrypt")]
+#[test]
+fn encrypted() {
+    let assets = include_asset_dir!("assets"; compress = false);
+    let asset = assets.assets[Path::new("noise.bin")];
+    let expected = fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/assets/noise.bin")).unwrap();
+    assert!(asset.encrypted);
+    assert_ne!(asset.data, expected);
+    // decrypting part of an asset has to line up with how the whole asset was encrypted
+    for range in [0..97, 1..2, 3..50, 13..96, 96..97] {
+        let (start, end) = (range.start as usize, range.end as usize);
+        assert_eq!(asset.read_range(range).unwrap(), &expected[start..end]);
+    }
+    assert_round_trips(assets);
+}
+
+#[test]
+fn renamed_assets_remember_their_source() {
+    let assets = include_asset_dir!("assets"; rename "nested/bnnuy.txt" = "renamed.txt");
+    assert!(assets.assets.contains_key(Path::new("renamed.txt")));
+    if cfg!(debug_assertions) {
+        assert_eq!(assets.sources[Path::new("renamed.txt")], Path::new("nested/bnnuy.txt"));
+        assert_eq!(assets.sources[Path::new("bnnuy.txt")], Path::new("bnnuy.txt"));
+    } else {
+        assert!(assets.sources.is_empty());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn converted_assets_change_extension() {
+    let assets = include_asset_dir!("assets"; process "nested/*.txt" as "md" = "cp {input} {output}");
+    let asset = assets.assets[Path::new("nested/bnnuy.md")];
+    assert_eq!(asset.contents().unwrap(), assets.assets[Path::new("bnnuy.txt")].contents().unwrap());
+    assert!(!assets.assets.contains_key(Path::new("nested/bnnuy.txt")));
+    // the original file is still a `.txt`, so it can't be hot reloaded
+    assert!(!assets.sources.contains_key(Path::new("nested/bnnuy.md")));
+    assert_eq!(assets.sources.contains_key(Path::new("bnnuy.txt")), cfg!(debug_assertions));
+}
+
+#[test]
+fn corrupt_packs_are_rejected() {
+    assert!(EmbeddedAssets::from_pack(b"").is_none());
+    assert!(EmbeddedAssets::from_pack(b"BIAPACK1\x01\x00\x00\x00").is_none());
+    assert!(EmbeddedAssets::from_pack(b"BIAPACK1\x00\x00\x00\x00")
+        .unwrap()
+        .assets
+        .is_empty());
+}
@@ -0,0 +1,274 @@
+//! # bevy_include_assets_build
+//!
+//! Embeds assets listed in a manifest file into a game using [bevy_include_assets][1],
+//! for use in build scripts. This crate doesn't depend on Bevy, so it doesn't
+//! have to be built for the host as well as the target.
+//!
+//! A manifest, e.g. `assets.ron`, lists what to embed:
+//!
+//! ```ron
+//! (
+//!     dir: "../assets",
+//!     include: ["*.png", "*.ttf"],
+//!     exclude: ["*.kra"],
+//!     rename: { "bnnuy.png": "player.png" },
+//!     compress: true,
+//!     process: [
+//!         (files: "*.png", command: "oxipng --opt 4 --out {output} {input}"),
+//!         (files: "textures/*.png", extension: "ktx2", command: "toktx --t2 {output} {input}"),
+//!     ],
+//!     max_size: "1 MiB",
+//! )
+//! ```
+//!
+//! or the same in TOML, as `assets.toml`. Every field but `dir` is optional,
+//! and they mean the same as the corresponding parts of `include_assets!`.
+//! `dir` is relative to the manifest.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     bevy_include_assets_build::embed_manifest("assets.ron").unwrap();
+//! }
+//!
+//! // main.rs
+//! let assets: EmbeddedAssets = include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+//! app.add_plugin(EmbeddedAssetsPlugin::new(assets));
+//! ```
+//!
+//! [1]: https://crates.io/crates/bevy_include_assets
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    dir: PathBuf,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Each file's path relative to `dir`, and the path it should be embedded as.
+    #[serde(default)]
+    rename: BTreeMap<String, String>,
+    compress: Option<bool>,
+    #[serde(default)]
+    process: Vec<Process>,
+    max_size: Option<Size>,
+    max_file_size: Option<Size>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Process {
+    files: String,
+    /// The extension of the command's output, if it converts files to another format.
+    extension: Option<String>,
+    command: String,
+}
+
+/// A number of bytes, or a string like `"5 MiB"`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Size {
+    Bytes(u64),
+    Text(String),
+}
+
+/// Generates code that embeds the assets listed in a RON or TOML manifest,
+/// writing it into `OUT_DIR` under the manifest's name with an `.rs`
+/// extension. The code evaluates to `bevy_include_assets::EmbeddedAssets`.
+///
+/// Returns the path of the generated file. Cargo is told to rerun the build
+/// script when the manifest changes, and the embedded files themselves are
+/// tracked by the generated code.
+pub fn embed_manifest(manifest: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let manifest = manifest.as_ref();
+    println!("cargo:rerun-if-changed={}", manifest.display());
+    let parsed = parse(manifest, &fs::read_to_string(manifest)?)?;
+
+    let base = manifest
+        .canonicalize()?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let code = generate(&parsed, &base);
+
+    let out_dir = env::var_os("OUT_DIR").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "OUT_DIR isn't set, so this isn't a build script",
+        )
+    })?;
+    let out = Path::new(&out_dir).join(manifest.with_extension("rs").file_name().unwrap_or_default());
+    fs::write(&out, code)?;
+    Ok(out)
+}
+
+/// Parses a manifest, picking its format from its extension.
+fn parse(manifest: &Path, contents: &str) -> io::Result<Manifest> {
+    match manifest.extension().and_then(|extension| extension.to_str()) {
+        // so that optional fields don't have to be wrapped in `Some(...)`
+        Some("ron") => ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(contents)
+            .map_err(|err| invalid_manifest(manifest, err)),
+        Some("toml") => toml::from_str(contents).map_err(|err| invalid_manifest(manifest, err)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} isn't a .ron or .toml file", manifest.display()),
+        )),
+    }
+}
+
+fn invalid_manifest(manifest: &Path, err: impl ToString) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} is invalid: {}", manifest.display(), err.to_string()),
+    )
+}
+
+/// Generates the `include_assets!` invocation equivalent to `manifest`.
+fn generate(manifest: &Manifest, base: &Path) -> String {
+    // the macro resolves relative paths against the generated file
+    let dir = format!("{:?}", base.join(&manifest.dir).to_string_lossy());
+    let mut filters = Vec::new();
+    filters.extend(manifest.include.iter().map(|pattern| format!("{:?}", pattern)));
+    filters.extend(manifest.exclude.iter().map(|pattern| format!("!{:?}", pattern)));
+
+    let mut options = Vec::new();
+    for (from, to) in &manifest.rename {
+        options.push(format!("rename {:?} = {:?}", from, to));
+    }
+    if let Some(compress) = manifest.compress {
+        options.push(format!("compress = {}", compress));
+    }
+    for process in &manifest.process {
+        let mut option = format!("process {:?}", process.files);
+        if let Some(extension) = &process.extension {
+            write!(option, " as {:?}", extension).unwrap();
+        }
+        write!(option, " = {:?}", process.command).unwrap();
+        options.push(option);
+    }
+    for (name, size) in [
+        ("max_size", &manifest.max_size),
+        ("max_file_size", &manifest.max_file_size),
+    ] {
+        match size {
+            Some(Size::Bytes(bytes)) => options.push(format!("{} = {}", name, bytes)),
+            Some(Size::Text(text)) => options.push(format!("{} = {:?}", name, text)),
+            None => {}
+        }
+    }
+
+    let mut code = String::new();
+    if filters.is_empty() {
+        write!(code, "::bevy_include_assets::include_asset_dir!({}", dir).unwrap();
+    } else {
+        write!(
+            code,
+            "::bevy_include_assets::include_assets!({} / {}",
+            dir,
+            filters.join(", ")
+        )
+        .unwrap();
+    }
+    if !options.is_empty() {
+        write!(code, "; {}", options.join(", ")).unwrap();
+    }
+    code.push_str(")\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generated(manifest: &str, contents: &str) -> String {
+        let manifest = parse(Path::new(manifest), contents).unwrap();
+        generate(&manifest, Path::new("/game"))
+    }
+
+    #[test]
+    fn ron_manifests() {
+        let code = generated(
+            "assets.ron",
+            r#"(
+                dir: "assets",
+                include: ["*.png", "*.ttf"],
+                exclude: ["*.kra"],
+                rename: { "bnnuy.png": "player.png" },
+                compress: true,
+                process: [
+                    (files: "*.png", command: "oxipng {input}"),
+                    (files: "*.png", extension: "ktx2", command: "toktx {output} {input}"),
+                ],
+                max_size: "1 MiB",
+                max_file_size: 1024,
+            )"#,
+        );
+        assert_eq!(
+            code,
+            "::bevy_include_assets::include_assets!(\"/game/assets\" / \"*.png\", \"*.ttf\", !\"*.kra\"; \
+             rename \"bnnuy.png\" = \"player.png\", compress = true, process \"*.png\" = \"oxipng {input}\", \
+             process \"*.png\" as \"ktx2\" = \"toktx {output} {input}\", max_size = \"1 MiB\", max_file_size = 1024)\n"
+        );
+    }
+
+    #[test]
+    fn toml_manifests() {
+        let code = generated(
+            "assets.toml",
+            r#"
+                dir = "assets"
+                include = ["*.png"]
+                compress = false
+
+                [[process]]
+                files = "*.png"
+                extension = "ktx2"
+                command = "toktx {output} {input}"
+            "#,
+        );
+        assert_eq!(
+            code,
+            "::bevy_include_assets::include_assets!(\"/game/assets\" / \"*.png\"; \
+             compress = false, process \"*.png\" as \"ktx2\" = \"toktx {output} {input}\")\n"
+        );
+    }
+
+    #[test]
+    fn manifests_without_filters_embed_the_whole_dir() {
+        let code = generated("assets.ron", r#"(dir: "assets")"#);
+        assert_eq!(code, "::bevy_include_assets::include_asset_dir!(\"/game/assets\")\n");
+    }
+
+    #[test]
+    fn paths_are_quoted() {
+        let code = generated(
+            "assets.ron",
+            r#"(dir: "say \"hi\"", include: ["back\\slash.png"], rename: { "back\\slash.png": "a\"b.png" })"#,
+        );
+        assert_eq!(
+            code.trim_end(),
+            r#"::bevy_include_assets::include_assets!("/game/say \"hi\"" / "back\\slash.png"; rename "back\\slash.png" = "a\"b.png")"#
+        );
+    }
+
+    #[test]
+    fn unknown_formats_and_fields_are_errors() {
+        let err = parse(Path::new("assets.json"), "{}").err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = parse(Path::new("assets.ron"), r#"(dir: "assets", exclude_all: true)"#)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
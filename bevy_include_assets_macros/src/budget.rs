@@ -0,0 +1,194 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::{fs, io};
+
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, Lit, Token};
+
+use crate::embed::Asset;
+use crate::pack::Pack;
+
+/// If set, a report of every embedded file is written into this directory.
+const REPORT_DIR_VAR: &str = "BEVY_INCLUDE_ASSETS_REPORT_DIR";
+
+/// Limits on how many bytes of assets a single macro invocation may embed,
+/// given after the patterns as `; max_size = "5 MiB", max_file_size = "1 MiB"`.
+#[derive(Default)]
+pub struct Budget {
+    max_size: Option<(u64, Span)>,
+    max_file_size: Option<(u64, Span)>,
+}
+
+/// A single `name = size` entry of a [`Budget`].
+pub struct Limit {
+    name: Ident,
+    size: u64,
+}
+
+impl Parse for Limit {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let size = match input.parse()? {
+            Lit::Int(size) => size.base10_parse()?,
+            Lit::Str(size) => parse_size(&size.value())
+                .ok_or_else(|| syn::Error::new(size.span(), "expected a size like \"512 KiB\" or \"5 MB\""))?,
+            lit => return Err(syn::Error::new(lit.span(), "expected a size in bytes")),
+        };
+        Ok(Self { name, size })
+    }
+}
+
+impl Budget {
+    pub fn set(&mut self, Limit { name, size }: Limit) -> syn::Result<()> {
+        let limit = match name.to_string().as_str() {
+            "max_size" => &mut self.max_size,
+            "max_file_size" => &mut self.max_file_size,
+            _ => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "expected `max_size`, `max_file_size`, `process`, `rename` or `compress`",
+                ))
+            }
+        };
+        *limit = Some((size, name.span()));
+        Ok(())
+    }
+
+    /// Errors if the packed assets don't fit in the budget.
+    ///
+    /// Sizes are measured as stored in the binary, i.e. after compression.
+    pub fn check(&self, assets: &[Asset], pack: &Pack) -> syn::Result<()> {
+        let mut stored = assets
+            .iter()
+            .zip(&pack.entries)
+            .map(|(asset, entry)| (&asset.key, entry.stored))
+            .collect::<Vec<_>>();
+        stored.sort_by(|a, b| b.1.cmp(&a.1));
+
+        if let Some((max_file_size, span)) = self.max_file_size {
+            let over = stored
+                .iter()
+                .filter(|(_, size)| *size > max_file_size)
+                .collect::<Vec<_>>();
+            if !over.is_empty() {
+                let files = over
+                    .iter()
+                    .map(|(key, size)| format!("`{}` ({})", key, format_size(*size)))
+                    .collect::<Vec<_>>();
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "{} over the {} per-file budget: {}",
+                        if over.len() == 1 { "1 file is" } else { "files are" },
+                        format_size(max_file_size),
+                        files.join(", "),
+                    ),
+                ));
+            }
+        }
+
+        let total = pack.stored_size();
+        if let Some((max_size, span)) = self.max_size.filter(|(max_size, _)| total > *max_size) {
+            let largest = stored
+                .iter()
+                .take(5)
+                .map(|(key, size)| format!("`{}` ({})", key, format_size(*size)))
+                .collect::<Vec<_>>();
+            return Err(syn::Error::new(
+                span,
+                format!(
+                    "embedded assets take up {}, over the {} budget; the largest are {}",
+                    format_size(total),
+                    format_size(max_size),
+                    largest.join(", "),
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Writes a report of every embedded file's size into the directory named by
+/// `BEVY_INCLUDE_ASSETS_REPORT_DIR`, if it is set.
+pub fn report(assets: &[Asset], pack: &Pack) -> io::Result<()> {
+    let dir = match proc_macro::tracked_env::var(REPORT_DIR_VAR) {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => return Ok(()),
+    };
+
+    // one report per invocation, named after where it is
+    let call_site = proc_macro::Span::call_site();
+    let source_file = call_site.source_file().path();
+    let name = format!(
+        "{}-{}-{}.txt",
+        proc_macro::tracked_env::var("CARGO_CRATE_NAME").unwrap_or_default(),
+        source_file.to_string_lossy().replace(['/', '\\', '.'], "_"),
+        call_site.start().line,
+    );
+
+    let mut report = format!("{}:{}\n\n", source_file.display(), call_site.start().line);
+    writeln!(report, "{:>12} {:>12}  path", "size", "embedded").unwrap();
+    for (asset, entry) in assets.iter().zip(&pack.entries) {
+        match entry.duplicate_of {
+            Some(original) => writeln!(
+                report,
+                "{:>12} {:>12}  {} (same as {})",
+                entry.size, 0, asset.key, assets[original].key
+            ),
+            None => writeln!(report, "{:>12} {:>12}  {}", entry.size, entry.stored, asset.key),
+        }
+        .unwrap();
+    }
+    let size = pack.entries.iter().map(|entry| entry.size).sum::<u64>();
+    let stored = pack.stored_size();
+    writeln!(report, "{:>12} {:>12}  total", size, stored).unwrap();
+
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(name), report)
+}
+
+/// Parses sizes like `1500`, `"512 KiB"` or `"2.5 MB"`.
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "KB" | "kB" => 1000,
+        "KiB" => 1 << 10,
+        "MB" => 1000 * 1000,
+        "MiB" => 1 << 20,
+        "GB" => 1000 * 1000 * 1000,
+        "GiB" => 1 << 30,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * multiplier as f64) as u64)
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{} B", size),
+        1024..=0xfffff => format!("{:.1} KiB", size as f64 / (1 << 10) as f64),
+        _ => format!("{:.1} MiB", size as f64 / (1 << 20) as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_understands_units() {
+        assert_eq!(parse_size("1500"), Some(1500));
+        assert_eq!(parse_size("512 KiB"), Some(512 * 1024));
+        assert_eq!(parse_size("2.5 MB"), Some(2_500_000));
+        assert_eq!(parse_size(" 1GiB "), Some(1 << 30));
+        assert_eq!(parse_size("1 kB"), Some(1000));
+        assert_eq!(parse_size("5 parsecs"), None);
+        assert_eq!(parse_size("MiB"), None);
+    }
+}
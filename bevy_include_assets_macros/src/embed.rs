@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{LitByteStr, LitStr};
+
+use crate::pack::Pack;
+
+/// A file that will be embedded into the binary.
+pub struct Asset {
+    /// The `/`-separated path this asset will be served under.
+    pub key: String,
+    /// The absolute path of the file on disk.
+    pub source: PathBuf,
+    /// The `/`-separated path of the original file relative to the embedded
+    /// directory, which stays the same when the asset is renamed or processed.
+    pub path: String,
+    /// Whether a processor changed the asset's format, in which case the
+    /// original file can't be served in its place.
+    pub converted: bool,
+}
+
+/// Resolves a path literal relative to the source file the macro was invoked in.
+pub fn resolve(path: &LitStr) -> PathBuf {
+    let source_file = proc_macro::Span::call_site().source_file();
+    let base = if source_file.is_real() {
+        source_file.path().parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default()
+    };
+    // source file paths are relative to wherever rustc was started from
+    let path = base.join(path.value());
+    match env::current_dir() {
+        Ok(cwd) => cwd.join(path),
+        Err(_) => path,
+    }
+}
+
+/// Recursively collects every file under `root`, sorted by key.
+///
+/// Symlinks are followed, but dangling or cyclic ones are skipped.
+pub fn walk(root: &Path) -> io::Result<Vec<Asset>> {
+    let mut assets = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![(root.to_path_buf(), String::new())];
+
+    while let Some((dir, prefix)) = pending.pop() {
+        if !visited.insert(dir.canonicalize()?) {
+            continue;
+        }
+
+        // a directory's mtime changes whenever an entry is added or removed
+        proc_macro::tracked_path::path(dir.to_string_lossy());
+
+        let mut entries = fs::read_dir(&dir)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|name| io::Error::new(io::ErrorKind::InvalidData, format!("{:?} is not valid UTF-8", name)))?;
+            let key = format!("{}{}", prefix, name);
+            let path = entry.path();
+            match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => pending.push((path, format!("{}/", key))),
+                Ok(_) => assets.push(Asset {
+                    path: key.clone(),
+                    key,
+                    source: path,
+                    converted: false,
+                }),
+                Err(_) => continue,
+            }
+        }
+    }
+
+    assets.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(assets)
+}
+
+/// Generates an expression building the `EmbeddedAssets` that `EmbeddedAssetsPlugin::new` accepts.
+pub fn expand(root: &Path, assets: &[Asset], pack: &Pack) -> io::Result<TokenStream> {
+    let root = root.canonicalize()?;
+    let root = root.to_string_lossy();
+    // files a processor converted can't be reloaded from disk as they are
+    let sources = assets.iter().filter(|asset| !asset.converted);
+    let keys = sources.clone().map(|asset| &asset.key);
+    let paths = sources.map(|asset| &asset.path);
+    let pack = LitByteStr::new(&pack.bytes, Span::call_site());
+
+    // release builds shouldn't reveal where they were built
+    Ok(quote! {{
+        static PACK: &[u8] = #pack;
+        #[cfg(debug_assertions)]
+        let (source_dir, sources) = (
+            ::std::option::Option::Some(::std::path::Path::new(#root)),
+            ::std::collections::HashMap::from([
+                #((::std::path::Path::new(#keys), ::std::path::Path::new(#paths)),)*
+            ]),
+        );
+        #[cfg(not(debug_assertions))]
+        let (source_dir, sources) = (::std::option::Option::None, ::std::collections::HashMap::new());
+        ::bevy_include_assets::EmbeddedAssets {
+            source_dir,
+            sources,
+            ..::bevy_include_assets::EmbeddedAssets::from_pack(PACK).expect("asset pack is corrupt")
+        }
+    }})
+}
@@ -0,0 +1,177 @@
+//! # bevy_include_assets_macros
+//!
+//! Procedural macros backing [bevy_include_assets][1].
+//! Use the re-exports from that crate instead of depending on this one directly.
+//!
+//! [1]: https://crates.io/crates/bevy_include_assets
+
+#![feature(proc_macro_span, proc_macro_tracked_env, track_path)]
+
+mod budget;
+mod embed;
+mod pack;
+mod pattern;
+mod process;
+mod table;
+
+use std::io;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, LitStr};
+
+use crate::embed::Asset;
+use crate::pattern::{IncludeAssetDir, IncludeAssets, Options};
+use crate::table::AssetTable;
+
+/// Recursively embeds every file in a directory.
+///
+/// The directory is resolved relative to the file this macro is invoked in,
+/// just like [`include_bytes!`]. Expands to an `EmbeddedAssets` keyed by each file's
+/// `/`-separated path relative to that directory, ready to be passed to
+/// `EmbeddedAssetsPlugin::new`.
+///
+/// Adding or removing files anywhere in the directory will trigger a rebuild.
+/// Files with identical contents are only embedded once, but can still be
+/// loaded from every path.
+///
+/// The total size of the embedded files, and the size of each one, can be capped:
+///
+/// ```ignore
+/// include_asset_dir!("../../assets"; max_size = "20 MiB", max_file_size = "4 MiB")
+/// ```
+///
+/// Sizes are either a number of bytes or a string with a unit (`B`, `KB`, `KiB`,
+/// `MB`, `MiB`, `GB` or `GiB`), and are measured after compression, with
+/// identical files counted once towards the total. Going over budget is a
+/// compile error listing the largest files.
+///
+/// If `BEVY_INCLUDE_ASSETS_REPORT_DIR` is set at build time, a report of every
+/// embedded file's size is written into that directory for each invocation.
+///
+/// Files can also be run through external tools before they're embedded:
+///
+/// ```ignore
+/// include_asset_dir!("../../assets";
+///     process "**/*.png" = "pngquant --force --output {output} {input}",
+///     process "**/*.png" = "oxipng --opt 4 --strip safe --out {output} {input}",
+///     process "LiberationSans-Bold.ttf" = "pyftsubset {input} --text-file={file:glyphs.txt} --output-file={output}",
+/// )
+/// ```
+///
+/// Every processor whose glob matches a file is run over it in order, in the
+/// embedded directory, with `{input}` replaced by the file to process, `{output}`
+/// by where to write the result, and `{file:path}` by `path` in the embedded
+/// directory. The results are embedded under the original paths, so nothing
+/// else has to change.
+///
+/// Since asset loaders are picked by extension, a processor that converts
+/// files to another format has to give the extension of its output, which
+/// replaces the file's own:
+///
+/// ```ignore
+/// include_asset_dir!("../../assets";
+///     process "textures/*.png" as "ktx2" = "toktx --t2 {output} {input}",
+/// )
+/// ```
+///
+/// The result is then embedded as `textures/bnnuy.ktx2`, and later processors
+/// and renames see it under that path.
+///
+/// Outputs are cached by the contents of the input, the command, and any
+/// `{file:path}`s, in `BEVY_INCLUDE_ASSETS_CACHE_DIR` if it is set and under
+/// `OUT_DIR` or the system temporary directory otherwise. Files read from disk,
+/// e.g. when hot reloading, are served unprocessed, so converted files aren't
+/// hot reloaded at all.
+///
+/// Finally, files can be embedded under a different path with
+/// `rename "skins/default/bnnuy.png" = "player.png"`, and compression can be
+/// turned off for a single invocation with `compress = false`.
+///
+/// With the `encrypt` feature of `bevy_include_assets` enabled, files are
+/// also encrypted with a key derived from `BEVY_INCLUDE_ASSETS_KEY`, which
+/// must be set at build time. This is obfuscation rather than security, as
+/// the binary has to contain the key to decrypt them.
+#[proc_macro]
+pub fn include_asset_dir(input: TokenStream) -> TokenStream {
+    let IncludeAssetDir { dir, options } = parse_macro_input!(input as IncludeAssetDir);
+    include(&dir, &options, Ok)
+        .map(|(_, embedded)| embedded)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Embeds files from a directory selected by name or glob.
+///
+/// ```ignore
+/// include_assets!("../../assets" / "bnnuy.png", "**/*.ttf", !"**/*.kra")
+/// ```
+///
+/// Patterns are matched against each file's `/`-separated path relative to the
+/// directory and are applied in order, so a later `!`-prefixed pattern removes
+/// files an earlier one added. If the first pattern is an exclusion, every file
+/// starts out included. A pattern that matches nothing is a compile error.
+///
+/// Expands to the same `EmbeddedAssets` as [`include_asset_dir!`], and takes
+/// the same budget and processors after the patterns:
+///
+/// ```ignore
+/// include_assets!("../../assets" / "**/*.png"; max_size = "8 MiB")
+/// ```
+#[proc_macro]
+pub fn include_assets(input: TokenStream) -> TokenStream {
+    let IncludeAssets { dir, filters, options } = parse_macro_input!(input as IncludeAssets);
+    include(&dir, &options, |assets| pattern::select(assets, &filters))
+        .map(|(_, embedded)| embedded)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Generates a struct with a typed handle for each asset selected like [`include_assets!`],
+/// so that renaming a file breaks the build instead of leaving a blank sprite.
+///
+/// ```ignore
+/// include_asset_table! {
+///     /// Every asset used by the game.
+///     pub struct GameAssets("../../assets" / "bnnuy.png", "*.ttf");
+/// }
+/// ```
+///
+/// Each asset gets a field named after its path in snake_case (`bnnuy_png`)
+/// and a constant holding the path itself (`GameAssets::BNNUY_PNG`). Fields are
+/// `Handle<T>`s with `T` inferred from the extension, e.g. `Image` for `.png`
+/// or `Font` for `.ttf`, and `HandleUntyped` for unrecognized extensions.
+/// These types are named through the invoking crate's `bevy` dependency.
+/// A budget and processors can be given inside the parentheses, as with [`include_assets!`].
+///
+/// The struct implements `AssetTable`, whose `embedded()` returns the assets
+/// for `EmbeddedAssetsPlugin`, and `AssetTablePlugin` loads it as a resource.
+#[proc_macro]
+pub fn include_asset_table(input: TokenStream) -> TokenStream {
+    let table = parse_macro_input!(input as AssetTable);
+    let IncludeAssets { dir, filters, options } = &table.assets;
+    include(dir, options, |assets| pattern::select(assets, filters))
+        .and_then(|(assets, embedded)| table::expand(&table, &assets, embedded))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Walks `dir`, narrows down its files with `select`, processes them, and
+/// embeds the result if it fits in the budget.
+fn include(
+    dir: &LitStr,
+    options: &Options,
+    select: impl FnOnce(Vec<Asset>) -> syn::Result<Vec<Asset>>,
+) -> syn::Result<(Vec<Asset>, proc_macro2::TokenStream)> {
+    let root = embed::resolve(dir);
+    let error = |err: io::Error| syn::Error::new(dir.span(), format!("couldn't read `{}`: {}", root.display(), err));
+    let assets = embed::walk(&root).map_err(error).and_then(select)?;
+    let assets = process::apply(&root, assets, &options.processors)?;
+    let assets = pattern::rename(assets, &options.renames)?;
+    let key = pack::key().map_err(|err| syn::Error::new(dir.span(), err))?;
+    let pack = pack::write(&assets, options.compress(), key).map_err(error)?;
+    budget::report(&assets, &pack)
+        .map_err(|err| syn::Error::new(dir.span(), format!("couldn't write asset size report: {}", err)))?;
+    options.budget.check(&assets, &pack)?;
+    let embedded = embed::expand(&root, &assets, &pack).map_err(error)?;
+    Ok((assets, embedded))
+}
@@ -0,0 +1,205 @@
+//! Writes the asset pack format that `bevy_include_assets` reads at runtime.
+//! The two must be kept in sync.
+//!
+//! A pack is laid out as:
+//!
+//! ```text
+//! magic    b"BIAPACK1"
+//! count    u32
+//! entries  count × { path_len u16, path [u8; path_len], flags u8,
+//!                    offset u64, stored_len u64, size u64, hash u64 }
+//! data     [u8]
+//! ```
+//!
+//! Integers are little-endian, and offsets are relative to the start of `data`.
+//! Entries with identical contents share the same data.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::{fs, io};
+
+use crate::embed::Asset;
+
+const MAGIC: &[u8; 8] = b"BIAPACK1";
+
+/// Set in an entry's flags if its data is deflated.
+#[cfg(feature = "compress")]
+const DEFLATED: u8 = 1;
+
+/// Set in an entry's flags if its data is encrypted.
+#[cfg(feature = "encrypt")]
+const ENCRYPTED: u8 = 2;
+
+/// The environment variable holding the key assets are encrypted with.
+#[cfg(feature = "encrypt")]
+const KEY_VAR: &str = "BEVY_INCLUDE_ASSETS_KEY";
+
+/// The `miniz_oxide` level assets are compressed at, from 0 (none) to 10 (slowest).
+#[cfg(feature = "compress")]
+const COMPRESSION_LEVEL: u8 = 9;
+
+/// A packed set of assets.
+pub struct Pack {
+    pub bytes: Vec<u8>,
+    /// The sizes of each asset, in the same order they were packed in.
+    pub entries: Vec<Entry>,
+}
+
+impl Pack {
+    /// The number of bytes the assets take up in the pack, counting shared data once.
+    pub fn stored_size(&self) -> u64 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.duplicate_of.is_none())
+            .map(|entry| entry.stored)
+            .sum()
+    }
+}
+
+pub struct Entry {
+    /// The size of the asset on disk.
+    pub size: u64,
+    /// The size of the asset as stored in the pack, after compression.
+    pub stored: u64,
+    /// The index of an earlier entry with the same contents, whose data this one shares.
+    pub duplicate_of: Option<usize>,
+}
+
+/// Reads every asset from disk and packs them together, deflating them if
+/// `compress` is set and encrypting them if there's an `encryption_key`.
+///
+/// Assets with identical contents are only stored once.
+pub fn write(assets: &[Asset], compress: bool, encryption_key: Option<u64>) -> io::Result<Pack> {
+    let mut entries = Vec::with_capacity(assets.len());
+    let mut table = Vec::new();
+    let mut data = Vec::new();
+    // the entries stored so far by hash, with where their data is
+    let mut stored_by_hash = HashMap::<u64, Vec<(usize, u8, usize)>>::new();
+    table.extend_from_slice(MAGIC);
+    table.extend_from_slice(&(assets.len() as u32).to_le_bytes());
+
+    for Asset { key, source, .. } in assets {
+        // `include_bytes!` would normally be what tracks the file for changes
+        proc_macro::tracked_path::path(source.to_string_lossy());
+        let contents = fs::read(source)?;
+        let hash = fnv1a(&contents);
+        let (flags, stored) = store(&contents, compress);
+        let (flags, stored) = match encryption_key {
+            Some(key) => encrypt(flags, stored, key, hash),
+            None => (flags, stored),
+        };
+
+        // compression and encryption are deterministic, so identical contents are stored identically
+        let candidates = stored_by_hash.entry(hash).or_default();
+        let duplicate_of = candidates
+            .iter()
+            .find(|(_, other_flags, offset)| {
+                *other_flags == flags && data.get(*offset..*offset + stored.len()) == Some(&*stored)
+            })
+            .copied();
+        let offset = match duplicate_of {
+            Some((_, _, offset)) => offset,
+            None => {
+                candidates.push((entries.len(), flags, data.len()));
+                data.extend_from_slice(&stored);
+                data.len() - stored.len()
+            }
+        };
+
+        table.extend_from_slice(&(key.len() as u16).to_le_bytes());
+        table.extend_from_slice(key.as_bytes());
+        table.push(flags);
+        table.extend_from_slice(&(offset as u64).to_le_bytes());
+        table.extend_from_slice(&(stored.len() as u64).to_le_bytes());
+        table.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        table.extend_from_slice(&hash.to_le_bytes());
+        entries.push(Entry {
+            size: contents.len() as u64,
+            stored: stored.len() as u64,
+            duplicate_of: duplicate_of.map(|(index, _, _)| index),
+        });
+    }
+
+    table.extend_from_slice(&data);
+    Ok(Pack { bytes: table, entries })
+}
+
+/// Returns an asset's flags and the bytes that should be stored for it.
+#[cfg(not(feature = "compress"))]
+fn store(contents: &[u8], _compress: bool) -> (u8, Cow<[u8]>) {
+    (0, Cow::Borrowed(contents))
+}
+
+/// Returns an asset's flags and the bytes that should be stored for it.
+#[cfg(feature = "compress")]
+fn store(contents: &[u8], compress: bool) -> (u8, Cow<[u8]>) {
+    if !compress {
+        return (0, Cow::Borrowed(contents));
+    }
+    let deflated = miniz_oxide::deflate::compress_to_vec(contents, COMPRESSION_LEVEL);
+    if deflated.len() < contents.len() {
+        (DEFLATED, Cow::Owned(deflated))
+    } else {
+        (0, Cow::Borrowed(contents))
+    }
+}
+
+/// Returns the key assets should be encrypted with, derived from
+/// `BEVY_INCLUDE_ASSETS_KEY`, or `None` if the `encrypt` feature isn't enabled.
+#[cfg(not(feature = "encrypt"))]
+pub fn key() -> Result<Option<u64>, String> {
+    Ok(None)
+}
+
+/// Returns the key assets should be encrypted with, derived from
+/// `BEVY_INCLUDE_ASSETS_KEY`, or `None` if the `encrypt` feature isn't enabled.
+#[cfg(feature = "encrypt")]
+pub fn key() -> Result<Option<u64>, String> {
+    match proc_macro::tracked_env::var(KEY_VAR) {
+        Ok(key) if !key.is_empty() => Ok(Some(fnv1a(key.as_bytes()))),
+        _ => Err(format!(
+            "the `encrypt` feature is enabled, but `{}` isn't set to a key",
+            KEY_VAR
+        )),
+    }
+}
+
+#[cfg(not(feature = "encrypt"))]
+fn encrypt(flags: u8, stored: Cow<[u8]>, _key: u64, _nonce: u64) -> (u8, Cow<[u8]>) {
+    (flags, stored)
+}
+
+/// Encrypts an asset's stored bytes, using the hash of its contents as the nonce.
+#[cfg(feature = "encrypt")]
+fn encrypt(flags: u8, stored: Cow<[u8]>, key: u64, nonce: u64) -> (u8, Cow<[u8]>) {
+    let mut encrypted = stored.into_owned();
+    apply_keystream(&mut encrypted, key, nonce);
+    (flags | ENCRYPTED, Cow::Owned(encrypted))
+}
+
+/// XORs `data` with a SplitMix64 keystream seeded from `key` and `nonce`,
+/// which both encrypts and decrypts it.
+///
+/// This only keeps assets from being trivially extracted from the binary,
+/// since the key is in there too.
+#[cfg(feature = "encrypt")]
+fn apply_keystream(data: &mut [u8], key: u64, nonce: u64) {
+    let mut state = key ^ nonce.rotate_left(32);
+    for chunk in data.chunks_mut(8) {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        for (byte, key) in chunk.iter_mut().zip(z.to_le_bytes()) {
+            *byte ^= key;
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of `data`.
+pub fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,343 @@
+use glob::{MatchOptions, Pattern};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{LitBool, LitStr, Token};
+
+use crate::budget::Budget;
+use crate::embed::Asset;
+use crate::process::{self, Processor};
+
+pub const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// The arguments to `include_asset_dir!`, i.e. `"dir"` followed by [`Options`].
+pub struct IncludeAssetDir {
+    pub dir: LitStr,
+    pub options: Options,
+}
+
+impl Parse for IncludeAssetDir {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let dir = input.parse()?;
+        let options = input.parse()?;
+        Ok(Self { dir, options })
+    }
+}
+
+/// The arguments to `include_assets!`, i.e. `"dir" / "a.png", "**/*.ttf", !"**/*.kra"`
+/// followed by [`Options`].
+pub struct IncludeAssets {
+    pub dir: LitStr,
+    pub filters: Vec<Filter>,
+    pub options: Options,
+}
+
+impl Parse for IncludeAssets {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let dir = input.parse()?;
+        input.parse::<Token![/]>()?;
+        let mut filters = Punctuated::<Filter, Token![,]>::new();
+        while !input.is_empty() && !input.peek(Token![;]) {
+            filters.push_value(input.parse()?);
+            if input.is_empty() || input.peek(Token![;]) {
+                break;
+            }
+            filters.push_punct(input.parse()?);
+        }
+        let options = input.parse()?;
+        Ok(Self {
+            dir,
+            filters: filters.into_iter().collect(),
+            options,
+        })
+    }
+}
+
+mod kw {
+    syn::custom_keyword!(compress);
+    syn::custom_keyword!(rename);
+}
+
+/// The optional settings after a `;`, i.e. `max_size = "5 MiB", process "*.png" = "..."`.
+#[derive(Default)]
+pub struct Options {
+    pub budget: Budget,
+    pub processors: Vec<Processor>,
+    pub renames: Vec<Rename>,
+    compress: Option<bool>,
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Self::default();
+        if input.parse::<Option<Token![;]>>()?.is_none() {
+            return Ok(options);
+        }
+        while !input.is_empty() {
+            if input.peek(process::kw::process) {
+                options.processors.push(input.parse()?);
+            } else if input.peek(kw::rename) {
+                options.renames.push(input.parse()?);
+            } else if input.peek(kw::compress) {
+                input.parse::<kw::compress>()?;
+                input.parse::<Token![=]>()?;
+                let compress = input.parse::<LitBool>()?;
+                if compress.value && !cfg!(feature = "compress") {
+                    return Err(syn::Error::new(
+                        compress.span(),
+                        "the `compress` feature of `bevy_include_assets` isn't enabled",
+                    ));
+                }
+                options.compress = Some(compress.value);
+            } else {
+                options.budget.set(input.parse()?)?;
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(options)
+    }
+}
+
+impl Options {
+    /// Whether assets should be compressed, which they are by default if the `compress` feature is enabled.
+    pub fn compress(&self) -> bool {
+        self.compress.unwrap_or(cfg!(feature = "compress"))
+    }
+}
+
+/// Embeds a file under a different path, i.e. `rename "skins/default/bnnuy.png" = "player.png"`.
+pub struct Rename {
+    from: LitStr,
+    to: LitStr,
+}
+
+impl Parse for Rename {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<kw::rename>()?;
+        let from = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let to = input.parse()?;
+        Ok(Self { from, to })
+    }
+}
+
+/// Applies every rename, keeping the assets sorted by key.
+///
+/// Errors if a renamed file isn't being embedded, or if two assets would end
+/// up with the same path.
+pub fn rename(mut assets: Vec<Asset>, renames: &[Rename]) -> syn::Result<Vec<Asset>> {
+    if renames.is_empty() {
+        return Ok(assets);
+    }
+    for Rename { from, to } in renames {
+        let asset = assets
+            .iter_mut()
+            .find(|asset| asset.key == from.value())
+            .ok_or_else(|| syn::Error::new(from.span(), format!("`{}` isn't being embedded", from.value())))?;
+        asset.key = to.value().trim_matches('/').to_owned();
+    }
+    assets.sort_by(|a, b| a.key.cmp(&b.key));
+    if let Some(duplicate) = assets.windows(2).find(|pair| pair[0].key == pair[1].key) {
+        let to = renames.iter().find(|rename| rename.to.value() == duplicate[0].key);
+        let span = to.map_or_else(proc_macro2::Span::call_site, |rename| rename.to.span());
+        return Err(syn::Error::new(
+            span,
+            format!("more than one asset would be embedded as `{}`", duplicate[0].key),
+        ));
+    }
+    Ok(assets)
+}
+
+/// A single file name or glob, optionally negated with `!` to exclude matches.
+pub struct Filter {
+    pub exclude: bool,
+    pub pattern: LitStr,
+}
+
+impl Parse for Filter {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let exclude = input.parse::<Option<Token![!]>>()?.is_some();
+        let pattern = input.parse()?;
+        Ok(Self { exclude, pattern })
+    }
+}
+
+impl Filter {
+    /// Returns the indices of every key this filter matches.
+    ///
+    /// Errors if the pattern is malformed or matches nothing, so that typos
+    /// are caught at compile time instead of at runtime.
+    pub fn matches(&self, keys: &[&str]) -> syn::Result<Vec<usize>> {
+        let pattern = Pattern::new(&self.pattern.value())
+            .map_err(|err| syn::Error::new(self.pattern.span(), format!("invalid pattern: {}", err)))?;
+        let matches = keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| pattern.matches_with(key, MATCH_OPTIONS))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            Err(syn::Error::new(
+                self.pattern.span(),
+                format!("`{}` doesn't match any files", self.pattern.value()),
+            ))
+        } else {
+            Ok(matches)
+        }
+    }
+}
+
+/// Applies each filter in order, returning the assets that remain included.
+pub fn select(assets: Vec<Asset>, filters: &[Filter]) -> syn::Result<Vec<Asset>> {
+    let keys = assets.iter().map(|asset| asset.key.as_str()).collect::<Vec<_>>();
+    let included = included(&keys, filters)?;
+    Ok(assets
+        .into_iter()
+        .zip(included)
+        .filter_map(|(asset, included)| included.then_some(asset))
+        .collect())
+}
+
+/// Returns whether each key is included once every filter has been applied in order.
+///
+/// If the first filter is an exclusion, every key starts out included.
+fn included(keys: &[&str], filters: &[Filter]) -> syn::Result<Vec<bool>> {
+    let include_all = filters.first().map_or(false, |filter| filter.exclude);
+    let mut included = vec![include_all; keys.len()];
+    for filter in filters {
+        for i in filter.matches(keys)? {
+            included[i] = !filter.exclude;
+        }
+    }
+    Ok(included)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    const KEYS: &[&str] = &[
+        "bnnuy.kra",
+        "bnnuy.png",
+        "fonts/bold.ttf",
+        "fonts/regular.ttf",
+        "ui/title.png",
+    ];
+
+    fn filters(filters: &str) -> Vec<Filter> {
+        syn::parse_str::<IncludeAssets>(&format!("\"assets\" / {}", filters))
+            .unwrap()
+            .filters
+    }
+
+    fn selected(filters: &[Filter]) -> Vec<&'static str> {
+        let included = included(KEYS, filters).unwrap();
+        KEYS.iter()
+            .zip(included)
+            .filter_map(|(key, included)| included.then_some(*key))
+            .collect()
+    }
+
+    fn assets(keys: &[&str]) -> Vec<Asset> {
+        keys.iter()
+            .map(|key| Asset {
+                key: key.to_string(),
+                source: PathBuf::from(key),
+                path: key.to_string(),
+                converted: false,
+            })
+            .collect()
+    }
+
+    fn renames(renames: &str) -> Vec<Rename> {
+        syn::parse_str::<Options>(&format!("; {}", renames)).unwrap().renames
+    }
+
+    #[test]
+    fn globs_select_files() {
+        assert_eq!(selected(&filters(r#""bnnuy.png""#)), ["bnnuy.png"]);
+        assert_eq!(selected(&filters(r#""*.png""#)), ["bnnuy.png"]);
+        assert_eq!(selected(&filters(r#""**/*.png""#)), ["bnnuy.png", "ui/title.png"]);
+        assert_eq!(
+            selected(&filters(r#""fonts/*", "bnnuy.kra""#)),
+            ["bnnuy.kra", "fonts/bold.ttf", "fonts/regular.ttf"]
+        );
+    }
+
+    #[test]
+    fn exclusions_apply_in_order() {
+        assert_eq!(
+            selected(&filters(r#""**/*", !"fonts/*""#)),
+            ["bnnuy.kra", "bnnuy.png", "ui/title.png"]
+        );
+        // a leading exclusion starts from every file
+        assert_eq!(
+            selected(&filters(r#"!"*.kra""#)),
+            ["bnnuy.png", "fonts/bold.ttf", "fonts/regular.ttf", "ui/title.png"]
+        );
+        // later patterns win
+        assert_eq!(
+            selected(&filters(r#"!"fonts/*", "fonts/bold.ttf""#)),
+            ["bnnuy.kra", "bnnuy.png", "fonts/bold.ttf", "ui/title.png"]
+        );
+        assert_eq!(
+            selected(&filters(r#""fonts/bold.ttf", !"fonts/*", "**/*.png""#)),
+            ["bnnuy.png", "ui/title.png"]
+        );
+    }
+
+    #[test]
+    fn patterns_matching_nothing_are_errors() {
+        let err = included(KEYS, &filters(r#""**/*.png", !"*.wav""#)).unwrap_err();
+        assert_eq!(err.to_string(), "`*.wav` doesn't match any files");
+        // `*` doesn't cross directories
+        let err = included(KEYS, &filters(r#""*.ttf""#)).unwrap_err();
+        assert_eq!(err.to_string(), "`*.ttf` doesn't match any files");
+        let err = included(KEYS, &filters(r#""[.png""#)).unwrap_err();
+        assert!(err.to_string().starts_with("invalid pattern"));
+    }
+
+    #[test]
+    fn renames_move_keys() {
+        let renamed = rename(assets(KEYS), &renames(r#"rename "ui/title.png" = "/title.png/""#)).unwrap();
+        let keys = renamed.iter().map(|asset| asset.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "bnnuy.kra",
+                "bnnuy.png",
+                "fonts/bold.ttf",
+                "fonts/regular.ttf",
+                "title.png"
+            ]
+        );
+        assert_eq!(renamed[4].path, "ui/title.png");
+    }
+
+    #[test]
+    fn renames_cant_collide() {
+        let err = rename(assets(KEYS), &renames(r#"rename "ui/title.png" = "bnnuy.png""#))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "more than one asset would be embedded as `bnnuy.png`");
+        let err = rename(
+            assets(KEYS),
+            &renames(r#"rename "fonts/bold.ttf" = "font.ttf", rename "fonts/regular.ttf" = "font.ttf""#),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "more than one asset would be embedded as `font.ttf`");
+        let err = rename(assets(KEYS), &renames(r#"rename "bnnuy.wav" = "bnnuy.ogg""#))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "`bnnuy.wav` isn't being embedded");
+    }
+}
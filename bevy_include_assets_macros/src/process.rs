@@ -0,0 +1,238 @@
+//! Runs external tools over assets before they are embedded, e.g. to
+//! recompress images or subset fonts.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, io};
+
+use glob::Pattern;
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token};
+
+use crate::embed::Asset;
+use crate::pack;
+use crate::pattern::MATCH_OPTIONS;
+
+/// If set, processed assets are cached in this directory instead of under
+/// `OUT_DIR` or the system temporary directory.
+const CACHE_DIR_VAR: &str = "BEVY_INCLUDE_ASSETS_CACHE_DIR";
+
+pub mod kw {
+    syn::custom_keyword!(process);
+}
+
+/// A command run over every asset matching a glob, i.e. `process "*.png" = "oxipng --out {output} {input}"`,
+/// or `process "*.png" as "ktx2" = "..."` if it changes the asset's format.
+pub struct Processor {
+    pattern: Pattern,
+    /// The extension the output is embedded with, if it differs from the input's.
+    extension: Option<LitStr>,
+    command: LitStr,
+}
+
+impl Parse for Processor {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<kw::process>()?;
+        let pattern = input.parse::<LitStr>()?;
+        let extension = match input.parse::<Option<Token![as]>>()? {
+            Some(_) => Some(input.parse::<LitStr>()?),
+            None => None,
+        };
+        if let Some(extension) = &extension {
+            let value = extension.value();
+            if value.is_empty() || value.contains(['.', '/', '\\']) {
+                return Err(syn::Error::new(extension.span(), "expected an extension like \"ktx2\""));
+            }
+        }
+        input.parse::<Token![=]>()?;
+        let command = input.parse::<LitStr>()?;
+        if command.value().split_whitespace().next().is_none() {
+            return Err(syn::Error::new(command.span(), "expected a command"));
+        }
+        Ok(Self {
+            pattern: Pattern::new(&pattern.value())
+                .map_err(|err| syn::Error::new(pattern.span(), format!("invalid pattern: {}", err)))?,
+            extension,
+            command,
+        })
+    }
+}
+
+/// Runs every matching processor over each asset in order, pointing the
+/// asset at the processed file and changing its key's extension if the
+/// processor changes its format.
+///
+/// `root` is the directory being embedded, which commands are run in.
+pub fn apply(root: &Path, mut assets: Vec<Asset>, processors: &[Processor]) -> syn::Result<Vec<Asset>> {
+    if processors.is_empty() {
+        return Ok(assets);
+    }
+    let cache_dir = cache_dir();
+    for asset in &mut assets {
+        for processor in processors {
+            if processor.pattern.matches_with(&asset.key, MATCH_OPTIONS) {
+                let key = match &processor.extension {
+                    Some(extension) => with_extension(&asset.key, &extension.value()),
+                    None => asset.key.clone(),
+                };
+                asset.source = processor.run(root, &cache_dir, &key, &asset.source).map_err(|err| {
+                    syn::Error::new(
+                        processor.command.span(),
+                        format!("couldn't process `{}`: {}", asset.key, err),
+                    )
+                })?;
+                asset.converted |= key != asset.key;
+                asset.key = key;
+            }
+        }
+    }
+    assets.sort_by(|a, b| a.key.cmp(&b.key));
+    if let Some(duplicate) = assets.windows(2).find(|pair| pair[0].key == pair[1].key) {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "`{}` and `{}` would both be embedded as `{}`",
+                duplicate[0].path, duplicate[1].path, duplicate[0].key
+            ),
+        ));
+    }
+    Ok(assets)
+}
+
+/// Replaces the last extension of a key, so `ui/title.png` becomes `ui/title.ktx2`.
+fn with_extension(key: &str, extension: &str) -> String {
+    let (dir, file_name) = key.rsplit_once('/').unwrap_or(("", key));
+    let stem = match file_name.rfind('.') {
+        Some(dot) if dot > 0 => &file_name[..dot],
+        _ => file_name,
+    };
+    if dir.is_empty() {
+        format!("{}.{}", stem, extension)
+    } else {
+        format!("{}/{}.{}", dir, stem, extension)
+    }
+}
+
+impl Processor {
+    /// Processes `input`, the current contents of the asset that will be
+    /// embedded at `key`, returning where the output was written.
+    ///
+    /// Outputs are cached by the contents of the input, the command, and
+    /// any files the command refers to, so unchanged assets aren't reprocessed.
+    fn run(&self, root: &Path, cache_dir: &Path, key: &str, input: &Path) -> io::Result<PathBuf> {
+        let command = self.command.value();
+        let file_name = Path::new(key).file_name().unwrap_or_default();
+
+        proc_macro::tracked_path::path(input.to_string_lossy());
+        let mut key = fs::read(input)?;
+        key.extend_from_slice(command.as_bytes());
+        for file in referenced_files(&command) {
+            let path = root.join(file);
+            proc_macro::tracked_path::path(path.to_string_lossy());
+            let contents = fs::read(&path)
+                .map_err(|err| io::Error::new(err.kind(), format!("couldn't read `{}`: {}", path.display(), err)))?;
+            key.extend_from_slice(&contents);
+        }
+        let hash = format!("{:016x}", pack::fnv1a(&key));
+
+        let output = cache_dir.join(format!("{}-{}", hash, file_name.to_string_lossy()));
+        if output.exists() {
+            return Ok(output);
+        }
+
+        // tools often pick the format from the extension, so the
+        // partial output keeps the file name
+        let partial_dir = cache_dir.join(format!("{}.partial", hash));
+        let partial = partial_dir.join(file_name);
+        fs::create_dir_all(&partial_dir)?;
+        let args = command
+            .split_whitespace()
+            .map(|arg| substitute(arg, root, input, &partial))
+            .collect::<Vec<_>>();
+        let result = Command::new(&args[0]).args(&args[1..]).current_dir(root).output();
+        let result = match result {
+            Ok(result) if result.status.success() && partial.exists() => fs::rename(&partial, &output),
+            Ok(result) if result.status.success() => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("`{}` didn't write `{{output}}`", args[0]),
+            )),
+            Ok(result) => {
+                let stderr = String::from_utf8_lossy(&result.stderr);
+                let mut message = format!("`{}` failed with {}", args[0], result.status);
+                if !stderr.trim().is_empty() {
+                    message = format!("{}: {}", message, stderr.trim());
+                }
+                Err(io::Error::new(io::ErrorKind::Other, message))
+            }
+            Err(err) => Err(io::Error::new(
+                err.kind(),
+                format!("couldn't run `{}`: {}", args[0], err),
+            )),
+        };
+        let _ = fs::remove_dir_all(&partial_dir);
+        result.map(|()| output)
+    }
+}
+
+/// Replaces the placeholders in a command argument:
+///
+/// - `{input}` with the file being processed,
+/// - `{output}` with where the processed file should be written,
+/// - `{file:path}` with `path` relative to the embedded directory.
+fn substitute(arg: &str, root: &Path, input: &Path, output: &Path) -> String {
+    let mut substituted = arg.to_owned();
+    for file in referenced_files(arg) {
+        substituted = substituted.replace(&format!("{{file:{}}}", file), &root.join(file).to_string_lossy());
+    }
+    substituted
+        .replace("{input}", &input.to_string_lossy())
+        .replace("{output}", &output.to_string_lossy())
+}
+
+/// Returns the paths of every `{file:path}` placeholder in `command`.
+fn referenced_files(command: &str) -> Vec<&str> {
+    command
+        .split("{file:")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(file, _)| file))
+        .collect()
+}
+
+fn cache_dir() -> PathBuf {
+    if let Ok(dir) = proc_macro::tracked_env::var(CACHE_DIR_VAR) {
+        return PathBuf::from(dir);
+    }
+    // `OUT_DIR` is only set for crates with a build script
+    let base = proc_macro::tracked_env::var("OUT_DIR").map_or_else(|_| env::temp_dir(), PathBuf::from);
+    base.join("bevy_include_assets")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn processors_can_change_the_extension() {
+        let processor = syn::parse_str::<Processor>(r#"process "*.png" as "ktx2" = "toktx {output} {input}""#).unwrap();
+        assert_eq!(processor.extension.unwrap().value(), "ktx2");
+        let processor = syn::parse_str::<Processor>(r#"process "*.png" = "oxipng {input}""#).unwrap();
+        assert!(processor.extension.is_none());
+    }
+
+    #[test]
+    fn extensions_are_just_the_extension() {
+        for extension in ["", ".ktx2", "a/b"] {
+            let processor = format!(r#"process "*.png" as {:?} = "toktx {{output}} {{input}}""#, extension);
+            assert!(syn::parse_str::<Processor>(&processor).is_err(), "{:?}", extension);
+        }
+    }
+
+    #[test]
+    fn only_the_last_extension_is_replaced() {
+        assert_eq!(with_extension("bnnuy.png", "ktx2"), "bnnuy.ktx2");
+        assert_eq!(with_extension("ui.old/title.png", "ktx2"), "ui.old/title.ktx2");
+        assert_eq!(with_extension("level.scn.ron", "bin"), "level.scn.bin");
+        assert_eq!(with_extension("ui/.hidden", "ktx2"), "ui/.hidden.ktx2");
+        assert_eq!(with_extension("LICENSE", "txt"), "LICENSE.txt");
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Attribute, Ident, Token, Visibility};
+
+use crate::embed::Asset;
+use crate::pattern::IncludeAssets;
+
+/// The arguments to `include_asset_table!`, i.e. `pub struct GameAssets("dir" / "a.png")`.
+pub struct AssetTable {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub assets: IncludeAssets,
+}
+
+impl Parse for AssetTable {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let name = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let assets = content.parse()?;
+        input.parse::<Option<Token![;]>>()?;
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            assets,
+        })
+    }
+}
+
+/// Generates the table's struct, a constant for each asset's path, and its
+/// `AssetTable` impl, which embeds the assets with `embedded`.
+pub fn expand(table: &AssetTable, assets: &[Asset], embedded: TokenStream) -> syn::Result<TokenStream> {
+    let AssetTable { attrs, vis, name, .. } = table;
+
+    let mut names = HashMap::new();
+    let mut fields = Vec::new();
+    let mut consts = Vec::new();
+    let mut loads = Vec::new();
+    for Asset { key, .. } in assets {
+        let field = field_name(key);
+        if let Some(other) = names.insert(field.clone(), key) {
+            return Err(syn::Error::new(
+                table.assets.dir.span(),
+                format!("`{}` and `{}` would both be named `{}`", other, key, field),
+            ));
+        }
+        let doc = format!("`{}`", key);
+        let konst = Ident::new(&field.to_uppercase(), Span::call_site());
+        let field = Ident::new(&field, Span::call_site());
+        let (ty, load) = match asset_type(key) {
+            Some(ty) => (quote!(::bevy::asset::Handle<#ty>), quote!(load)),
+            None => (quote!(::bevy::asset::HandleUntyped), quote!(load_untyped)),
+        };
+        fields.push(quote!(#[doc = #doc] #vis #field: #ty,));
+        consts.push(quote!(#[doc = #doc] #vis const #konst: &'static str = #key;));
+        loads.push(quote!(#field: asset_server.#load(#key),));
+    }
+    let keys = assets.iter().map(|asset| &asset.key);
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis struct #name {
+            #(#fields)*
+        }
+
+        impl #name {
+            #(#consts)*
+        }
+
+        impl ::bevy_include_assets::AssetTable for #name {
+            const PATHS: &'static [&'static str] = &[#(#keys),*];
+
+            fn embedded() -> ::bevy_include_assets::EmbeddedAssets {
+                #embedded
+            }
+
+            fn load(asset_server: &::bevy::asset::AssetServer) -> Self {
+                Self {
+                    #(#loads)*
+                }
+            }
+        }
+    })
+}
+
+/// Converts an asset's path into a snake_case field name, so
+/// `ui/LiberationSans-Bold.ttf` becomes `ui_liberation_sans_bold_ttf`.
+fn field_name(key: &str) -> String {
+    let mut name = String::new();
+    let mut prev = None;
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            let word_start = c.is_ascii_uppercase()
+                && prev.map_or(false, |prev: char| prev.is_ascii_lowercase() || prev.is_ascii_digit());
+            if word_start {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+        prev = Some(c);
+    }
+    let mut name = name.trim_end_matches('_').to_owned();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    // keywords like `mod` can't be used as field names
+    if syn::parse_str::<Ident>(&name).is_err() {
+        name.push('_');
+    }
+    name
+}
+
+/// Infers the Bevy asset type an asset will be loaded as from its extension,
+/// returning `None` if it should be loaded untyped.
+fn asset_type(key: &str) -> Option<TokenStream> {
+    let key = key.to_lowercase();
+    if key.ends_with(".scn.ron") {
+        return Some(quote!(::bevy::scene::DynamicScene));
+    }
+    let ty = match key.rsplit_once('.')?.1 {
+        "png" | "jpg" | "jpeg" | "bmp" | "tga" | "dds" | "ktx2" | "basis" | "hdr" | "exr" | "webp" | "pam" | "pbm"
+        | "pgm" | "ppm" => quote!(::bevy::render::texture::Image),
+        "ttf" | "otf" => quote!(::bevy::text::Font),
+        "ogg" | "oga" | "spx" | "wav" | "mp3" | "flac" => quote!(::bevy::audio::AudioSource),
+        "gltf" | "glb" => quote!(::bevy::gltf::Gltf),
+        "scn" => quote!(::bevy::scene::DynamicScene),
+        "wgsl" | "vert" | "frag" | "comp" | "spv" => quote!(::bevy::render::render_resource::Shader),
+        _ => return None,
+    };
+    Some(ty)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn type_of(key: &str) -> Option<String> {
+        asset_type(key).map(|ty| ty.to_string())
+    }
+
+    #[test]
+    fn field_names_are_snake_case() {
+        assert_eq!(field_name("bnnuy.png"), "bnnuy_png");
+        assert_eq!(field_name("ui/LiberationSans-Bold.ttf"), "ui_liberation_sans_bold_ttf");
+        assert_eq!(field_name("sfx/hit 2.ogg"), "sfx_hit_2_ogg");
+    }
+
+    #[test]
+    fn field_names_are_valid_identifiers() {
+        assert_eq!(field_name("2d/bnnuy.png"), "_2d_bnnuy_png");
+        assert_eq!(field_name("self"), "self_");
+        assert_eq!(field_name("crate"), "crate_");
+        assert_eq!(field_name("mod"), "mod_");
+        // `_` on its own is a pattern, not a name
+        assert_eq!(field_name("..."), "__");
+    }
+
+    #[test]
+    fn asset_types_follow_the_extension() {
+        let image = quote!(::bevy::render::texture::Image).to_string();
+        let scene = quote!(::bevy::scene::DynamicScene).to_string();
+        assert_eq!(type_of("bnnuy.png"), Some(image.clone()));
+        assert_eq!(type_of("BNNUY.PNG"), Some(image));
+        assert_eq!(type_of("levels/one.scn.ron"), Some(scene.clone()));
+        assert_eq!(type_of("levels/one.scn"), Some(scene));
+        assert_eq!(type_of("settings.ron"), None);
+        assert_eq!(type_of("bnnuy.kra"), None);
+        assert_eq!(type_of("LICENSE"), None);
+    }
+
+    #[test]
+    fn duplicate_field_names_are_errors() {
+        let table = syn::parse_str::<AssetTable>(r#"pub struct GameAssets("assets" / "**/*")"#).unwrap();
+        let assets = ["ui/bnnuy.png", "ui_bnnuy.png"]
+            .into_iter()
+            .map(|key| Asset {
+                key: key.to_owned(),
+                source: PathBuf::from(key),
+                path: key.to_owned(),
+                converted: false,
+            })
+            .collect::<Vec<_>>();
+        let err = expand(&table, &assets, TokenStream::new()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "`ui/bnnuy.png` and `ui_bnnuy.png` would both be named `ui_bnnuy_png`"
+        );
+        assert!(err.to_compile_error().to_string().contains("compile_error"));
+    }
+}
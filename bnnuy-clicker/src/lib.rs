@@ -5,7 +5,6 @@
 #[cfg(all(not(debug_assertions), feature = "bevy_dyn"))]
 compile_error!("Bevy should not be dynamically linked for release builds!");
 
-use bevy::asset::AssetPlugin;
 use bevy::math::{vec2, vec3};
 use bevy::prelude::shape::Quad;
 use bevy::prelude::*;
@@ -17,6 +16,10 @@ use bevy_rapier2d::prelude::*;
 #[cfg(target_family = "wasm")]
 use wasm_bindgen::prelude::*;
 
+include_asset_table! {
+    struct BnnuyAssets("../../assets" / "bnnuy.png"; max_size = "256 KiB");
+}
+
 #[derive(Component, Default)]
 struct Bnnuy;
 
@@ -86,14 +89,10 @@ pub fn start() {
         .insert_resource(ClearColor(Color::rgba_u8(46, 178, 255, 64)))
         .init_resource::<LastCursorPos>()
         .add_plugins_with(DefaultPlugins, |group| {
-            if cfg!(not(debug_assertions)) {
-                group.add_before::<AssetPlugin, _>(EmbeddedAssetsPlugin::new(include_assets!(
-                    "../../assets" / "bnnuy.png"
-                )));
-            }
-            group
+            EmbeddedAssetsPlugin::new(BnnuyAssets::embedded()).add_to(group)
         })
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugin(AssetTablePlugin::<BnnuyAssets>::default())
         .add_startup_system(setup)
         .add_system(update_ceiling)
         .add_system(dup)
@@ -104,7 +103,7 @@ pub fn start() {
 
 fn setup(
     mut commands: Commands,
-    assets: Res<AssetServer>,
+    assets: Res<BnnuyAssets>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut colors: ResMut<Assets<ColorMaterial>>,
     windows: Res<Windows>,
@@ -143,7 +142,7 @@ fn setup(
         .insert(Ceiling);
 
     let default_bnnuy_color = Color::rgb_u8(0, 246, 255);
-    let bnnuy_texture = assets.load("bnnuy.png");
+    let bnnuy_texture = assets.bnnuy_png.clone();
     let bnnuy_factory = BnnuyFactory {
         mesh: meshes.add(Quad::new(vec2(10.0, 10.0)).into()).into(),
         texture: bnnuy_texture.clone(),